@@ -1,51 +1,43 @@
-/// Example: Interactive cat finder
-/// Helps locate rare cat coordinates
+//! Example: Interactive cat finder
+//! Helps locate rare cat coordinates
 
-use endless_utopia::World;
+use endless_utopia::{Explorer, World};
 
 fn main() {
     println!("🐱 EndlessUtopia - Cat Finder");
     println!("================================\n");
     
-    let world = World::new();
+    let world = World::builder().with_creatures().build();
     
-    // Search a large area systematically
-    println!("Searching for cats in a 2000x2000 area around origin...\n");
+    // Every tile in range counts, the search reads the spawn tables
+    let search_radius = 50_000;
+    println!("Searching for cats in a {0}x{0} area around origin...\n", 2 * search_radius + 1);
     
-    let mut cat_locations = Vec::new();
-    let search_radius = 1000;
+    let cat_locations = world.find_cat_nearby(0, 0, search_radius);
     
-    // Sample strategically instead of checking every coordinate
-    for y in (-search_radius..=search_radius).step_by(13) {
-        for x in (-search_radius..=search_radius).step_by(13) {
-            if world.is_cat_location(x, y) {
-                cat_locations.push((x, y));
-            }
-        }
-    }
-    
-    println!("Found {} cats in sampled area!", cat_locations.len());
+    println!("Found {} cats!", cat_locations.len());
     println!("\nCat Coordinates:");
     println!("================");
     
     for (i, (x, y)) in cat_locations.iter().take(20).enumerate() {
-        println!("{:2}. ({:5}, {:5})", i + 1, x, y);
+        println!("{:2}. ({:6}, {:6})", i + 1, x, y);
     }
     
     if cat_locations.len() > 20 {
         println!("... and {} more!", cat_locations.len() - 20);
     }
     
-    // Show one cat in detail
-    if let Some(&(x, y)) = cat_locations.first() {
-        println!("\n\nViewing first cat at ({}, {}):", x, y);
+    // Show the nearest cat in detail
+    if let Some((x, y)) = world.nearest_cat(0, 0) {
+        println!("\n\nViewing the cat nearest to the origin at ({}, {}):", x, y);
         println!("================================");
-        let mut world_mut = World::new();
-        let view = world_mut.render_region(x - 15, y - 5, 30, 10);
+        let mut explorer = Explorer::new(world);
+        let view = explorer.render_region(x - 15, y - 5, 30, 10);
         println!("{}", view);
         
-        println!("\nAfter visiting (showing trace):");
-        let view_with_trace = world_mut.render_region(x - 15, y - 5, 30, 10);
+        println!("\n40 ticks later (showing the Ascicat's trail, if it is nearby):");
+        explorer.set_tick(40);
+        let view_with_trace = explorer.render_region(x - 15, y - 5, 30, 10);
         println!("{}", view_with_trace);
     }
     
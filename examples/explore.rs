@@ -1,12 +1,13 @@
-/// Example: Exploring the infinite ASCII world
-/// Demonstrates world generation and cat finding
+//! Example: Exploring the infinite ASCII world
+//! Demonstrates world generation and cat finding
 
-use endless_utopia::World;
+use endless_utopia::{Explorer, World};
 
 fn main() {
     println!("🌌 EndlessUtopia - Infinite ASCII World Explorer\n");
     
-    let mut world = World::new();
+    let mut explorer = Explorer::new(World::new());
+    let world = explorer.world().clone();
     
     // Explore origin area
     println!("=== Region around origin (0,0) ===");
@@ -40,12 +41,13 @@ fn main() {
         if let Some(&(x, y)) = cats_wide.first() {
             println!("Found a cat at ({}, {})!", x, y);
             println!("\n=== Cat location ===");
-            let cat_region = world.render_region(x - 5, y - 2, 20, 5);
+            let cat_region = explorer.render_region(x - 5, y - 2, 20, 5);
             println!("{}", cat_region);
             
-            // Visit the cat again to see its trace
-            println!("\n=== Cat trace (after visiting) ===");
-            let trace_region = world.render_region(x - 5, y - 2, 20, 5);
+            // A few ticks later the cat has moved on, leaving a trail
+            println!("\n=== Cat trail (40 ticks later) ===");
+            explorer.set_tick(40);
+            let trace_region = explorer.render_region(x - 5, y - 2, 20, 5);
             println!("{}", trace_region);
         } else {
             println!("Cats are very rare! Try coordinates like (0, 0), (46, 0), or explore more.");
@@ -59,9 +61,9 @@ fn main() {
     // Show determinism
     println!("\n=== Testing determinism ===");
     println!("Generating the same location twice:");
-    let mut world2 = World::new();
+    let world2 = World::new();
     let test1 = world2.render_region(42, 17, 20, 5);
-    let mut world3 = World::new();
+    let world3 = World::new();
     let test2 = world3.render_region(42, 17, 20, 5);
     println!("First generation:");
     println!("{}", test1);
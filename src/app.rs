@@ -1,20 +1,62 @@
 /// Main WASM App - manages everything
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window, MouseEvent, KeyboardEvent, WheelEvent, HtmlElement, HtmlInputElement};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window, MouseEvent, KeyboardEvent, WheelEvent, TouchEvent, HtmlElement, HtmlInputElement};
 use std::rc::Rc;
 use std::cell::RefCell;
-use crate::world::World;
+use std::collections::HashMap;
+use crate::chunk::{ChunkCoord, CHUNK_SIZE};
+use crate::raster::chunk_rgb;
+use crate::theme::Role;
+use crate::viewport::{Viewport, WorldPoint, DEFAULT_TILE_HEIGHT, DEFAULT_TILE_WIDTH};
+use crate::world::{Tile, World, CAT_TICK_MILLIS};
+use crate::explorer::Explorer;
+use crate::region::Region;
+use crate::console::{Shell, ShellState};
+use crate::session::Session;
+use crate::link::{Link, DEFAULT_ZOOM};
+
+/// Glyph size at zoom 1 (fills a DEFAULT_TILE_WIDTH x DEFAULT_TILE_HEIGHT cell)
+const FONT_SIZE: f64 = 12.0;
+
+/// Opacity of a full-intensity tile (ordinary tiles come out at about 0.4)
+const WORLD_ALPHA: f64 = 0.5;
+
+/// Below this zoom chunks are drawn as colour blocks instead of glyphs
+const LOD_ZOOM: f64 = 0.4;
+/// Grid lines every this many tiles (at zoom 1), on chunk boundaries
+const GRID_TILES: (i64, i64) = (8, 4);
+/// Chunk colours kept between frames in level-of-detail mode (or twice the visible
+/// chunks, if more)
+const LOD_CACHE_CHUNKS: usize = 1 << 16;
+
+/// localStorage key of the saved session
+const SESSION_KEY: &str = "endless-utopia-session";
+/// How often the session is saved to localStorage (it is also saved when the page is hidden)
+const SESSION_SAVE_MILLIS: i32 = 5000;
+/// The URL fragment is rewritten once the view has been still for this long
+const LINK_UPDATE_MILLIS: i32 = 500;
 
 pub struct App {
     canvas: HtmlCanvasElement,
     ctx: CanvasRenderingContext2d,
-    world: World,
-    offset_x: f64,
-    offset_y: f64,
-    cat_x: f64,
-    cat_y: f64,
-    drawings: Vec<Vec<(f64, f64)>>,
+    explorer: Explorer,
+    region: Region,
+    line_buffer: String,
+    shell: Shell,
+    /// Camera: centre in tiles, zoom and the tile <-> screen transforms
+    viewport: Viewport,
+    /// Position and cat (tiles) as seen by commands, command history, colour theme
+    state: ShellState,
+    /// Freehand strokes in world (tile) coordinates
+    drawings: Vec<Vec<WorldPoint>>,
+    /// Average colour per chunk for level-of-detail rendering
+    lod_colours: HashMap<ChunkCoord, String>,
+    /// Finger distance at the previous step of a two-finger pinch
+    pinch_distance: Option<f64>,
+    /// Fragment last written to the URL, and the one seen at the previous check
+    written_link: String,
+    pending_link: String,
     is_drawing: bool,
     is_panning: bool,
     space_pressed: bool,
@@ -32,7 +74,6 @@ pub struct App {
     terminal_resize_edge: String,  // "right", "bottom", "corner"
     terminal_input: HtmlInputElement,
     terminal_output: Vec<String>,
-    terminal_history: Vec<String>,
     terminal_blink: bool,
     terminal_focused: bool,
 }
@@ -81,13 +122,17 @@ impl App {
         // Auto-focus terminal input on page load
         terminal_input.focus().ok();
 
-        // Random cat position
-        let cat_x = (js_sys::Math::random() * 4000.0) - 2000.0;
-        let cat_y = (js_sys::Math::random() * 4000.0) - 2000.0;
+        // Pick up where the last visit left off
+        let session = load_session(&window).unwrap_or_else(|| Session::new(crate::world::DEFAULT_SEED));
+        let mut state = ShellState::new((js_sys::Math::random() * u64::MAX as f64) as u64);
+        state.history = session.history.clone();
 
         let canvas_width = window.inner_width()?.as_f64().unwrap();
         let canvas_height = window.inner_height()?.as_f64().unwrap();
 
+        let mut viewport = Viewport::new(DEFAULT_TILE_WIDTH, DEFAULT_TILE_HEIGHT, canvas_width, canvas_height);
+        viewport.set_centre_point(session.view);
+
         let mut terminal_output = Vec::new();
         terminal_output.push("EndlessUtopia Terminal".to_string());
         terminal_output.push("Type 'help' for commands".to_string());
@@ -95,12 +140,17 @@ impl App {
         let app = Rc::new(RefCell::new(App {
             canvas,
             ctx,
-            world: World::new(),
-            offset_x: 0.0,
-            offset_y: 0.0,
-            cat_x,
-            cat_y,
-            drawings: Vec::new(),
+            explorer: session.explorer(World::builder().with_creatures().with_structures()),
+            region: Region::new(0, 0, 0, 0),
+            line_buffer: String::new(),
+            shell: Shell::new(),
+            viewport,
+            state,
+            drawings: session.drawings,
+            lod_colours: HashMap::new(),
+            pinch_distance: None,
+            written_link: String::new(),
+            pending_link: String::new(),
             is_drawing: false,
             is_panning: false,
             space_pressed: false,
@@ -117,11 +167,16 @@ impl App {
             terminal_resize_edge: String::new(),
             terminal_input: terminal_input.clone(),
             terminal_output,
-            terminal_history: Vec::new(),
             terminal_blink: false,
             terminal_focused: false,
         }));
 
+        // Put the cat where the world says it is right now
+        app.borrow_mut().update_cat();
+
+        // A shared link wins over the saved session
+        app.borrow_mut().follow_location_link();
+
         // Setup event handlers
         app.borrow().setup_events(app.clone(), &window, &terminal_input)?;
         
@@ -134,6 +189,12 @@ impl App {
         // Start terminal cursor blink
         app.borrow().start_terminal_blink(app.clone())?;
 
+        // Keep the session in localStorage
+        app.borrow().start_autosave(app.clone(), &window)?;
+
+        // Keep the URL fragment in sync with the view
+        app.borrow().start_link_updates(app.clone(), &window)?;
+
         Ok(app)
     }
 
@@ -154,6 +215,123 @@ impl App {
         Ok(())
     }
 
+    fn start_autosave(&self, app: Rc<RefCell<Self>>, window: &Window) -> Result<(), JsValue> {
+        {
+            let app = app.clone();
+            let closure = Closure::wrap(Box::new(move || {
+                app.borrow().save_session();
+            }) as Box<dyn FnMut()>);
+
+            window.set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                SESSION_SAVE_MILLIS,
+            )?;
+            closure.forget();
+        }
+
+        // Last chance before the tab is closed or reloaded
+        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            app.borrow().save_session();
+        }) as Box<dyn FnMut(_)>);
+
+        window.add_event_listener_with_callback("pagehide", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+        Ok(())
+    }
+
+    fn start_link_updates(&self, app: Rc<RefCell<Self>>, window: &Window) -> Result<(), JsValue> {
+        {
+            let app = app.clone();
+            let closure = Closure::wrap(Box::new(move || {
+                app.borrow_mut().update_link();
+            }) as Box<dyn FnMut()>);
+
+            window.set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                LINK_UPDATE_MILLIS,
+            )?;
+            closure.forget();
+        }
+
+        // Pasting a link into the address bar of an open tab
+        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            app.borrow_mut().follow_location_link();
+        }) as Box<dyn FnMut(_)>);
+
+        window.add_event_listener_with_callback("hashchange", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+        Ok(())
+    }
+
+    /// The current view as a shareable link
+    fn current_link(&self) -> Link {
+        let (x, y) = self.viewport.centre_tile();
+        Link::new(Some(self.explorer.world().seed()), x, y, self.viewport.zoom)
+    }
+
+    /// Jump to a link, switching worlds if it names another seed
+    fn apply_link(&mut self, link: Link) {
+        if let Some(seed) = link.seed.filter(|&seed| seed != self.explorer.world().seed()) {
+            self.explorer = Explorer::new(World::builder().with_creatures().with_structures().seed(seed).build());
+            self.lod_colours.clear();
+            self.update_cat();
+        }
+        self.viewport.centre_on(link.x, link.y);
+        self.viewport.zoom = 1.0;
+        self.viewport.zoom_at(link.zoom, self.viewport.width / 2.0, self.viewport.height / 2.0);
+    }
+
+    /// Follow the link in the page URL, if there is one
+    fn follow_location_link(&mut self) {
+        let Some(hash) = web_sys::window().and_then(|window| window.location().hash().ok()) else {
+            return;
+        };
+        if hash.len() <= 1 || hash == self.written_link {
+            return;
+        }
+        match hash.parse::<Link>() {
+            Ok(link) => {
+                self.apply_link(link);
+                self.written_link = self.current_link().to_string();
+                self.pending_link = self.written_link.clone();
+            }
+            Err(err) => self.terminal_output.push(format!("error: bad link: {}", err)),
+        }
+    }
+
+    /// Rewrite the URL fragment once the view stops moving (debounced, no history entries)
+    fn update_link(&mut self) {
+        let link = self.current_link().to_string();
+        if link != self.written_link && link == self.pending_link {
+            let history = web_sys::window().and_then(|window| window.history().ok());
+            if let Some(history) = history {
+                if history.replace_state_with_url(&JsValue::NULL, "", Some(&link)).is_ok() {
+                    self.written_link = link.clone();
+                }
+            }
+        }
+        self.pending_link = link;
+    }
+
+    /// Write seed, trail, viewport, drawings and history to localStorage
+    fn save_session(&self) {
+        let mut session = Session::from_explorer(&self.explorer);
+        session.view = self.viewport.centre_point();
+        session.drawings = self.drawings.clone();
+        session.history = self.state.history.clone();
+
+        let mut bytes = Vec::new();
+        if session.save(&mut bytes).is_err() {
+            return;
+        }
+        let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+        if let (Some(storage), Ok(text)) = (storage, String::from_utf8(bytes)) {
+            if storage.set_item(SESSION_KEY, &text).is_err() {
+                web_sys::console::warn_1(&"could not save session (storage full?)".into());
+            }
+        }
+    }
+
     fn setup_events(&self, app: Rc<RefCell<Self>>, window: &Window, terminal_input: &HtmlInputElement) -> Result<(), JsValue> {
         // Terminal focus handler
         {
@@ -250,14 +428,25 @@ impl App {
             let closure = Closure::wrap(Box::new(move |event: WheelEvent| {
                 event.prevent_default();
                 let mut app = app.borrow_mut();
-                app.offset_x += event.delta_x() * 0.5;
-                app.offset_y += event.delta_y() * 0.5;
+                app.on_wheel(event);
             }) as Box<dyn FnMut(_)>);
             
             self.canvas.add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())?;
             closure.forget();
         }
 
+        // Two-finger pinch on touch screens
+        for name in ["touchstart", "touchmove", "touchend", "touchcancel"] {
+            let app = app.clone();
+            let closure = Closure::wrap(Box::new(move |event: TouchEvent| {
+                let mut app = app.borrow_mut();
+                app.on_touch(event);
+            }) as Box<dyn FnMut(_)>);
+
+            self.canvas.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+            closure.forget();
+        }
+
         // Keyboard down
         {
             let app = app.clone();
@@ -393,7 +582,7 @@ impl App {
         } else if button == 0 {
             // Drawing
             self.is_drawing = true;
-            let world_pos = self.screen_to_world(mx, my);
+            let world_pos = self.viewport.point_at(mx, my);
             self.drawings.push(vec![world_pos]);
         }
     }
@@ -466,18 +655,48 @@ impl App {
         if self.is_panning {
             let dx = mx - self.last_x;
             let dy = my - self.last_y;
-            self.offset_x -= dx;
-            self.offset_y -= dy;
+            self.viewport.drag(dx, dy);
             self.last_x = mx;
             self.last_y = my;
         } else if self.is_drawing {
-            let world_pos = self.screen_to_world(mx, my);
+            let world_pos = self.viewport.point_at(mx, my);
             if let Some(last_drawing) = self.drawings.last_mut() {
                 last_drawing.push(world_pos);
             }
         }
     }
 
+    fn on_wheel(&mut self, event: WheelEvent) {
+        // Deltas come in pixels, lines or pages
+        let unit = match event.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => 16.0,
+            WheelEvent::DOM_DELTA_PAGE => 400.0,
+            _ => 1.0,
+        };
+        // Trackpad pinches arrive as wheel events with ctrl held and small deltas
+        let speed = if event.ctrl_key() { 0.01 } else { 0.0015 };
+        let factor = (-event.delta_y() * unit * speed).exp();
+        self.viewport.zoom_at(factor, event.client_x() as f64, event.client_y() as f64);
+        self.viewport.drag(-event.delta_x() * unit * 0.5, 0.0);
+    }
+
+    fn on_touch(&mut self, event: TouchEvent) {
+        let touches = event.touches();
+        let (Some(a), Some(b), 2) = (touches.get(0), touches.get(1), touches.length()) else {
+            self.pinch_distance = None;
+            return;
+        };
+        event.prevent_default();
+
+        let (ax, ay) = (a.client_x() as f64, a.client_y() as f64);
+        let (bx, by) = (b.client_x() as f64, b.client_y() as f64);
+        let distance = (ax - bx).hypot(ay - by).max(1.0);
+        if let Some(previous) = self.pinch_distance {
+            self.viewport.zoom_at(distance / previous, (ax + bx) / 2.0, (ay + by) / 2.0);
+        }
+        self.pinch_distance = Some(distance);
+    }
+
     fn on_mouse_up(&mut self) {
         self.terminal_dragging = false;
         self.terminal_resizing = false;
@@ -502,13 +721,18 @@ impl App {
             return;
         }
 
+        // Screen pixels per key press
         let speed = if event.shift_key() { 100.0 } else { 20.0 };
+        let (centre_x, centre_y) = (self.viewport.width / 2.0, self.viewport.height / 2.0);
 
         match key.as_str() {
-            "ArrowLeft" => self.offset_x -= speed,
-            "ArrowRight" => self.offset_x += speed,
-            "ArrowUp" => self.offset_y -= speed,
-            "ArrowDown" => self.offset_y += speed,
+            "+" | "=" => self.viewport.zoom_at(1.25, centre_x, centre_y),
+            "-" | "_" => self.viewport.zoom_at(0.8, centre_x, centre_y),
+            "0" => self.viewport.zoom_at(DEFAULT_ZOOM / self.viewport.zoom, centre_x, centre_y),
+            "ArrowLeft" => self.viewport.drag(speed, 0.0),
+            "ArrowRight" => self.viewport.drag(-speed, 0.0),
+            "ArrowUp" => self.viewport.drag(0.0, speed),
+            "ArrowDown" => self.viewport.drag(0.0, -speed),
             _ => {}
         }
     }
@@ -537,23 +761,25 @@ impl App {
         Ok(())
     }
 
+    /// Sync the cat with the world's deterministic walk (same cat on every machine)
+    fn update_cat(&mut self) {
+        let tick = js_sys::Date::now() as u64 / CAT_TICK_MILLIS;
+        self.explorer.set_tick(tick);
+        let (cat_x, cat_y) = self.explorer.cat_position();
+        self.state.cat_x = cat_x;
+        self.state.cat_y = cat_y;
+    }
+
     fn start_cat_movement(&self, app: Rc<RefCell<Self>>) -> Result<(), JsValue> {
         let closure = Closure::wrap(Box::new(move || {
-            let mut app = app.borrow_mut();
-            app.cat_x += (js_sys::Math::random() - 0.5) * 40.0;
-            app.cat_y += (js_sys::Math::random() - 0.5) * 40.0;
-            
-            if js_sys::Math::random() < 0.1 {
-                app.cat_x += (js_sys::Math::random() - 0.5) * 200.0;
-                app.cat_y += (js_sys::Math::random() - 0.5) * 200.0;
-            }
+            app.borrow_mut().update_cat();
         }) as Box<dyn FnMut()>);
 
         web_sys::window()
             .unwrap()
             .set_interval_with_callback_and_timeout_and_arguments_0(
                 closure.as_ref().unchecked_ref(),
-                2000,
+                CAT_TICK_MILLIS as i32,
             )?;
         
         closure.forget();
@@ -563,9 +789,10 @@ impl App {
     fn render(&mut self) -> Result<(), JsValue> {
         let width = self.canvas.width() as f64;
         let height = self.canvas.height() as f64;
+        self.viewport.set_size(width, height);
 
         // Clear
-        self.ctx.set_fill_style_str("#111");
+        self.ctx.set_fill_style_str(&self.state.theme.ui(Role::Background).to_string());
         self.ctx.fill_rect(0.0, 0.0, width, height);
 
         // World
@@ -586,96 +813,151 @@ impl App {
         Ok(())
     }
 
-    fn screen_to_world(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
-        let width = self.canvas.width() as f64;
-        let height = self.canvas.height() as f64;
-        (
-            screen_x + self.offset_x - width / 2.0,
-            screen_y + self.offset_y - height / 2.0,
-        )
-    }
-
-    fn world_to_screen(&self, world_x: f64, world_y: f64, width: f64, height: f64) -> (f64, f64) {
-        (
-            world_x - self.offset_x + width / 2.0,
-            world_y - self.offset_y + height / 2.0,
-        )
-    }
-
     fn render_world(&mut self, width: f64, height: f64) -> Result<(), JsValue> {
-        let char_width = 7.2;
-        let char_height = 16.0;
-        
-        let view_left = self.offset_x - width / 2.0;
-        let view_top = self.offset_y - height / 2.0;
-        
-        // Safe conversion with bounds checking
-        let world_col_start = (view_left / char_width).floor();
-        let world_row_start = (view_top / char_height).floor();
-        
-        // Clamp to safe i32 range to prevent overflow
-        let world_col_start = world_col_start.clamp(i32::MIN as f64, i32::MAX as f64) as i32 - 20;
-        let world_row_start = world_row_start.clamp(i32::MIN as f64, i32::MAX as f64) as i32 - 20;
-        
-        // Cap rendering area to reasonable size (viewport + buffer)
-        let cols = ((width / char_width).ceil() as usize + 60).min(500);
-        let rows = ((height / char_height).ceil() as usize + 50).min(300);
+        if self.viewport.zoom < LOD_ZOOM {
+            return self.render_lod(width, height);
+        }
 
-        let region_text = self.world.render_region(world_col_start, world_row_start, cols, rows);
-        let lines: Vec<&str> = region_text.lines().collect();
+        // Visible tiles plus a small margin, capped to a reasonable size
+        let visible = self.viewport.visible_rect().grow(2);
+        let cols = visible.width.min(2000);
+        let rows = visible.height.min(1000);
+
+        // Shy creatures keep away from the centre of the view
+        self.explorer.set_viewer(Some(self.viewport.centre_tile()));
+
+        // Reuse the region and line buffers between frames
+        self.region.set_bounds(visible.x, visible.y, cols, rows);
+        self.explorer.fill_region(&mut self.region);
+
+        self.ctx.set_font(&format!("{}px monospace", FONT_SIZE * self.viewport.zoom));
+        self.ctx.set_text_baseline("top");
+
+        // One fill_text per run of equally styled tiles, in the theme's colours
+        let (tile_width, tile_height) = self.viewport.tile_size();
+        let theme = &self.state.theme;
+        let style = |tile: &Tile| (theme.tile_colour(tile), tile.bg, tile.intensity);
+        for row_idx in 0..self.region.height() {
+            let (sx, sy) = self.viewport.tile_to_screen(visible.x, visible.y.wrapping_add(row_idx as i64));
+            let row = self.region.row(row_idx);
+            let mut start = 0;
+            while start < row.len() {
+                let first = row[start];
+                let end = row[start..].iter().position(|tile| style(tile) != style(&first)).map_or(row.len(), |len| start + len);
+                let x = sx + start as f64 * tile_width;
+
+                if let Some(bg) = first.bg {
+                    self.ctx.set_global_alpha(1.0);
+                    self.ctx.set_fill_style_str(&bg.to_string());
+                    self.ctx.fill_rect(x, sy, (end - start) as f64 * tile_width, tile_height);
+                }
+                self.line_buffer.clear();
+                self.line_buffer.extend(row[start..end].iter().map(|tile| tile.character));
+                if !self.line_buffer.trim().is_empty() {
+                    self.ctx.set_global_alpha(WORLD_ALPHA * first.intensity as f64 / u8::MAX as f64);
+                    self.ctx.set_fill_style_str(&theme.tile_colour(&first).to_string());
+                    self.ctx.fill_text(&self.line_buffer, x, sy)?;
+                }
+                start = end;
+            }
+        }
 
-        self.ctx.set_fill_style_str("#0f0");
-        self.ctx.set_font("12px monospace");
-        self.ctx.set_global_alpha(0.4);
+        self.ctx.set_global_alpha(1.0);
+        self.ctx.set_text_baseline("alphabetic");
+        Ok(())
+    }
 
-        for (row_idx, line) in lines.iter().enumerate() {
-            let world_x = world_col_start as f64 * char_width;
-            let world_y = (world_row_start + row_idx as i32) as f64 * char_height;
-            let (sx, sy) = self.world_to_screen(world_x, world_y, width, height);
-            self.ctx.fill_text(line, sx, sy)?;
+    /// Zoomed far out: one block per chunk in its average biome colour, no glyphs
+    fn render_lod(&mut self, _width: f64, _height: f64) -> Result<(), JsValue> {
+        let visible = self.viewport.visible_rect();
+        let first = ChunkCoord::containing(visible.x, visible.y);
+        let last = ChunkCoord::containing(
+            visible.x.saturating_add_unsigned(visible.width as u64),
+            visible.y.saturating_add_unsigned(visible.height as u64),
+        );
+
+        // Only off-screen chunks are dropped, so a view wider than the cache does not
+        // recolour every chunk each frame
+        let visible_chunks = ((last.x - first.x + 1) * (last.y - first.y + 1)) as usize;
+        if self.lod_colours.len() > LOD_CACHE_CHUNKS.max(2 * visible_chunks) {
+            let (columns, rows) = (first.x..=last.x, first.y..=last.y);
+            self.lod_colours.retain(|coord, _| columns.contains(&coord.x) && rows.contains(&coord.y));
         }
 
-        self.ctx.set_global_alpha(1.0);
+        // Blocks overlap by half a pixel so no seams show between them
+        let (tile_width, tile_height) = self.viewport.tile_size();
+        let block_width = CHUNK_SIZE as f64 * tile_width + 0.5;
+        let block_height = CHUNK_SIZE as f64 * tile_height + 0.5;
+        for chunk_y in first.y..=last.y {
+            for chunk_x in first.x..=last.x {
+                let coord = ChunkCoord { x: chunk_x, y: chunk_y };
+                let world = self.explorer.world();
+                let theme = &self.state.theme;
+                let colour = self.lod_colours.entry(coord).or_insert_with(|| {
+                    let [r, g, b] = chunk_rgb(world, theme, coord);
+                    format!("rgb({},{},{})", r, g, b)
+                });
+                self.ctx.set_fill_style_str(colour);
+
+                let (origin_x, origin_y) = coord.origin();
+                let (sx, sy) = self.viewport.tile_to_screen(origin_x, origin_y);
+                self.ctx.fill_rect(sx, sy, block_width, block_height);
+            }
+        }
         Ok(())
     }
 
-    fn render_grid(&self, width: f64, height: f64) -> Result<(), JsValue> {
-        self.ctx.set_stroke_style_str("#1a1a1a");
+    fn render_grid(&self, _width: f64, _height: f64) -> Result<(), JsValue> {
+        self.ctx.set_stroke_style_str(&self.state.theme.ui(Role::Grid).to_string());
         self.ctx.set_line_width(1.0);
 
-        let grid_size = 100.0;
-        let start_x = ((self.offset_x - width / 2.0) / grid_size).floor() * grid_size;
-        let end_x = ((self.offset_x + width / 2.0) / grid_size).ceil() * grid_size;
-        let start_y = ((self.offset_y - height / 2.0) / grid_size).floor() * grid_size;
-        let end_y = ((self.offset_y + height / 2.0) / grid_size).ceil() * grid_size;
+        // Grid lines at least 50 screen pixels apart at any zoom
+        let (tile_width, tile_height) = self.viewport.tile_size();
+        let spacing = |tiles: i64, size: f64| {
+            let mut spacing = tiles;
+            while (spacing as f64) * size < 50.0 {
+                spacing *= 2;
+            }
+            spacing
+        };
+        let (grid_x, grid_y) = (spacing(GRID_TILES.0, tile_width), spacing(GRID_TILES.1, tile_height));
+
+        // Whole tiles, so the lines stay put however far out the view is
+        let visible = self.viewport.visible_rect();
+        let first = |start: i64, step: i64| start.div_euclid(step).saturating_mul(step);
+        let (start_x, start_y) = (first(visible.x, grid_x), first(visible.y, grid_y));
+        let end_x = visible.x.saturating_add_unsigned(visible.width as u64);
+        let end_y = visible.y.saturating_add_unsigned(visible.height as u64);
+        let (left, top) = self.viewport.tile_to_screen(start_x, start_y);
+        let (right, bottom) = self.viewport.tile_to_screen(end_x, end_y);
 
         let mut gx = start_x;
         while gx <= end_x {
-            let (sx, sy1) = self.world_to_screen(gx, start_y, width, height);
-            let (_, sy2) = self.world_to_screen(gx, end_y, width, height);
+            let (sx, _) = self.viewport.tile_to_screen(gx, start_y);
             self.ctx.begin_path();
-            self.ctx.move_to(sx, sy1);
-            self.ctx.line_to(sx, sy2);
+            self.ctx.move_to(sx, top);
+            self.ctx.line_to(sx, bottom);
             self.ctx.stroke();
-            gx += grid_size;
+            let Some(next) = gx.checked_add(grid_x) else { break };
+            gx = next;
         }
 
         let mut gy = start_y;
         while gy <= end_y {
-            let (sx1, sy) = self.world_to_screen(start_x, gy, width, height);
-            let (sx2, _) = self.world_to_screen(end_x, gy, width, height);
+            let (_, sy) = self.viewport.tile_to_screen(start_x, gy);
             self.ctx.begin_path();
-            self.ctx.move_to(sx1, sy);
-            self.ctx.line_to(sx2, sy);
+            self.ctx.move_to(left, sy);
+            self.ctx.line_to(right, sy);
             self.ctx.stroke();
-            gy += grid_size;
+            let Some(next) = gy.checked_add(grid_y) else { break };
+            gy = next;
         }
 
         Ok(())
     }
 
-    fn render_drawings(&self, width: f64, height: f64) -> Result<(), JsValue> {
-        self.ctx.set_stroke_style_str("#00ff00");
+    fn render_drawings(&self, _width: f64, _height: f64) -> Result<(), JsValue> {
+        self.ctx.set_stroke_style_str(&self.state.theme.ui(Role::Drawing).to_string());
         self.ctx.set_line_width(3.0);
         self.ctx.set_line_cap("round");
 
@@ -685,8 +967,8 @@ impl App {
             }
 
             self.ctx.begin_path();
-            for (i, &(wx, wy)) in drawing.iter().enumerate() {
-                let (sx, sy) = self.world_to_screen(wx, wy, width, height);
+            for (i, &point) in drawing.iter().enumerate() {
+                let (sx, sy) = self.viewport.point_to_screen(point);
                 if i == 0 {
                     self.ctx.move_to(sx, sy);
                 } else {
@@ -699,17 +981,23 @@ impl App {
         Ok(())
     }
 
-    fn render_cat(&self, width: f64, height: f64) -> Result<(), JsValue> {
+    fn render_cat(&self, _width: f64, _height: f64) -> Result<(), JsValue> {
         let cat_art = [" /\\_/\\  ", "( o.o ) ", " > ^ <  "];
 
         self.ctx.save();
-        self.ctx.set_fill_style_str("#ff0");
-        self.ctx.set_font("bold 16px monospace");
+        // Shrinks with the world, but stays big enough to spot when zoomed out
+        let scale = self.viewport.zoom.max(0.5);
+        let colour = self.state.theme.ui(Role::Cat).to_string();
+        self.ctx.set_fill_style_str(&colour);
+        self.ctx.set_font(&format!("bold {}px monospace", 16.0 * scale));
+        self.ctx.set_text_baseline("top");
         self.ctx.set_shadow_blur(10.0);
-        self.ctx.set_shadow_color("#ff0");
+        self.ctx.set_shadow_color(&colour);
 
+        // The art starts on the cat's tile
+        let (cat_x, cat_y) = self.viewport.tile_to_screen(self.state.cat_x, self.state.cat_y);
         for (i, line) in cat_art.iter().enumerate() {
-            let (sx, sy) = self.world_to_screen(self.cat_x, self.cat_y + i as f64 * 20.0, width, height);
+            let (sx, sy) = (cat_x, cat_y + i as f64 * 20.0 * scale);
             self.ctx.fill_text(line, sx, sy)?;
         }
 
@@ -721,9 +1009,10 @@ impl App {
         // Logo oben links
         self.ctx.save();
         
-        self.ctx.set_fill_style_str("#0f0");
+        let colour = self.state.theme.ui(Role::World).to_string();
+        self.ctx.set_fill_style_str(&colour);
         self.ctx.set_shadow_blur(5.0);
-        self.ctx.set_shadow_color("#0f0");
+        self.ctx.set_shadow_color(&colour);
         self.ctx.set_font("bold 11px monospace");
         
         let logo = vec![
@@ -749,15 +1038,16 @@ impl App {
         // Bottom left: Position display
         self.ctx.save();
         
-        self.ctx.set_fill_style_str("#0f0");
+        self.ctx.set_fill_style_str(&colour);
         self.ctx.set_shadow_blur(2.0);
-        self.ctx.set_shadow_color("#0f0");
+        self.ctx.set_shadow_color(&colour);
         self.ctx.set_font("bold 11px monospace");
         
         let base_y = height - 12.0;
         
         // Current position
-        let pos_text = format!("Position: X: {:.0}  Y: {:.0}", self.offset_x, self.offset_y);
+        let (tile_x, tile_y) = self.viewport.centre_tile();
+        let pos_text = format!("Position: X: {}  Y: {}  Zoom: {:.2}x", tile_x, tile_y, self.viewport.zoom);
         self.ctx.fill_text(&pos_text, 10.0, base_y)?;
         
         self.ctx.restore();
@@ -795,7 +1085,7 @@ impl App {
         self.ctx.fill_text("explorer@endlessutopia:~", tx + 8.0, ty + 14.0)?;
         
         // History area starts right after header
-        self.ctx.set_fill_style_str("#ffaa00");
+        self.ctx.set_fill_style_str(&self.state.theme.ui(Role::Terminal).to_string());
         self.ctx.set_font("13px 'Courier New', monospace");
         
         let history_start_y = ty + 35.0;
@@ -883,71 +1173,34 @@ impl App {
     }
 
     fn execute_command(&mut self, cmd: &str) {
-        let parts: Vec<&str> = cmd.split_whitespace().collect();
-        
-        self.terminal_history.push(cmd.to_string());
-        
-        // Show the command that was entered (with prompt)
-        self.terminal_output.push(format!("explorer@endlessutopia:~$ {}", cmd));
-        
-        if parts.is_empty() {
-            return;
+        // Commands see and move the tile at the camera centre
+        let centre = self.viewport.centre_tile();
+        (self.state.x, self.state.y) = centre;
+        let theme = self.state.theme.clone();
+        let output = self.shell.execute(cmd, &mut self.state, self.explorer.world());
+        if (self.state.x, self.state.y) != centre {
+            self.viewport.centre_on(self.state.x, self.state.y);
+        }
+        // Zoomed-out blocks were coloured by the old theme
+        if self.state.theme != theme {
+            self.lod_colours.clear();
         }
+        if output.clear {
+            self.terminal_output.clear();
+        }
+        self.terminal_output.extend(output.lines);
+    }
+}
 
-        match parts[0].to_lowercase().as_str() {
-            "help" => {
-                self.terminal_output.push("Commands:".to_string());
-                self.terminal_output.push("  help        show this help".to_string());
-                self.terminal_output.push("  clear       clear terminal".to_string());
-                self.terminal_output.push("  goto X Y    jump to coordinates".to_string());
-                self.terminal_output.push("  cat         find the ascii cat".to_string());
-                self.terminal_output.push("  random      random location".to_string());
-                self.terminal_output.push("  pos         show current position".to_string());
-                self.terminal_output.push("".to_string());
-            }
-            "clear" | "cls" => {
-                self.terminal_output.clear();
-            }
-            "goto" => {
-                if parts.len() >= 3 {
-                    if let (Ok(x), Ok(y)) = (parts[1].parse::<f64>(), parts[2].parse::<f64>()) {
-                        let max_coord = 1_000_000.0;
-                        if !x.is_finite() || !y.is_finite() {
-                            self.terminal_output.push("error: invalid coordinates".to_string());
-                        } else if x.abs() > max_coord || y.abs() > max_coord {
-                            self.terminal_output.push(format!("error: coordinates out of bounds (must be between -{} and +{})", max_coord, max_coord));
-                        } else {
-                            self.offset_x = x;
-                            self.offset_y = y;
-                            self.terminal_output.push(format!("teleported to ({}, {})", x, y));
-                        }
-                    } else {
-                        self.terminal_output.push("error: invalid numbers".to_string());
-                    }
-                } else {
-                    self.terminal_output.push("usage: goto <x> <y>".to_string());
-                }
-            }
-            "cat" => {
-                self.offset_x = self.cat_x;
-                self.offset_y = self.cat_y;
-                self.terminal_output.push("found ascii cat! 🐱".to_string());
-            }
-            "random" | "rnd" => {
-                self.offset_x = (js_sys::Math::random() * 4000.0) - 2000.0;
-                self.offset_y = (js_sys::Math::random() * 4000.0) - 2000.0;
-                self.terminal_output.push(format!("warped to ({:.0}, {:.0})", self.offset_x, self.offset_y));
-            }
-            "pos" | "position" | "where" => {
-                self.terminal_output.push(format!("x={:.0} y={:.0}", self.offset_x, self.offset_y));
-            }
-            "" => {
-                // Empty command, do nothing
-            }
-            _ => {
-                self.terminal_output.push(format!("command not found: {}", parts[0]));
-                self.terminal_output.push("type 'help' for available commands".to_string());
-            }
+/// The session saved in localStorage, if there is a readable one
+fn load_session(window: &Window) -> Option<Session> {
+    let storage = window.local_storage().ok().flatten()?;
+    let text = storage.get_item(SESSION_KEY).ok().flatten()?;
+    match Session::load(text.as_bytes()) {
+        Ok(session) => Some(session),
+        Err(err) => {
+            web_sys::console::warn_1(&format!("ignoring saved session: {}", err).into());
+            None
         }
     }
 }
@@ -0,0 +1,123 @@
+//! endless-utopia - native terminal explorer for the infinite ASCII world
+//! Opens a full-screen view of the world, or renders regions headlessly; see `endless-utopia --help`
+
+mod render;
+mod tui;
+
+use std::process::ExitCode;
+
+use endless_utopia::world::{seed_from_str, WorldBuilder};
+use endless_utopia::{Link, Theme};
+
+const USAGE: &str = "\
+Usage: endless-utopia [explore] [OPTIONS]
+       endless-utopia render [RENDER OPTIONS]   (see `endless-utopia render --help`)
+
+Options:
+  --seed <N>        numeric world seed
+  --seed-str <S>    text world seed (hashed)
+  --glyphs <G>      characters to draw with: unicode, cp437, latin1 or ascii
+  --at <X> <Y>      start at tile (X, Y)
+  --link <LINK>     open a shared link (`#seed=..&x=..&y=..` or a whole URL)
+  --theme <T>       colour theme: classic, amber, solarized, high-contrast,
+                    monochrome or a theme file
+  -h, --help        show this help
+
+Keys:
+  arrows / WASD     pan (hold shift to move faster)
+  :  or  Enter      open the command prompt (try 'help')
+  q  or  Ctrl-C     quit";
+
+/// Handle the world options (seed, glyphs) shared by all subcommands.
+/// Returns `Ok(false)` if `arg` is not a world option.
+fn parse_world_option<'a>(
+    arg: &str,
+    iter: &mut impl Iterator<Item = &'a String>,
+    builder: &mut WorldBuilder,
+) -> Result<bool, String> {
+    match arg {
+        "--seed" => {
+            let value = iter.next().ok_or("--seed needs a value")?;
+            let seed = value.parse::<u64>().map_err(|_| format!("invalid seed: {}", value))?;
+            *builder = std::mem::take(builder).seed(seed);
+            Ok(true)
+        }
+        "--seed-str" => {
+            let value = iter.next().ok_or("--seed-str needs a value")?;
+            *builder = std::mem::take(builder).seed(seed_from_str(value));
+            Ok(true)
+        }
+        "--glyphs" => {
+            let value = iter.next().ok_or("--glyphs needs a value")?;
+            *builder = std::mem::take(builder).glyphs(value.parse()?);
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// A built-in theme by name, or a theme file
+fn load_theme(name_or_path: &str) -> Result<Theme, String> {
+    if let Some(theme) = Theme::named(name_or_path) {
+        return Ok(theme);
+    }
+    let text = std::fs::read_to_string(name_or_path)
+        .map_err(|err| format!("no theme called {} and cannot read it as a file: {}", name_or_path, err))?;
+    Theme::parse(&text).map_err(|err| format!("{}: {}", name_or_path, err))
+}
+
+/// Parse the value following a flag
+fn parse_value<'a, T: std::str::FromStr>(flag: &str, iter: &mut impl Iterator<Item = &'a String>) -> Result<T, String> {
+    let value = iter.next().ok_or(format!("{} needs a value", flag))?;
+    value.parse().map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+fn explore(args: &[String]) -> Result<(), String> {
+    // The interactive explorer shows birds, mites, stray cats and structures too
+    let mut builder = WorldBuilder::new().with_creatures().with_structures();
+    let mut start = (0, 0);
+    let mut theme = Theme::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if parse_world_option(arg, &mut iter, &mut builder)? {
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--at" => start = (parse_value("--at", &mut iter)?, parse_value("--at", &mut iter)?),
+            "--link" => {
+                let link: Link = parse_value("--link", &mut iter)?;
+                if let Some(seed) = link.seed {
+                    builder = builder.seed(seed);
+                }
+                start = (link.x, link.y);
+            }
+            "--theme" => theme = load_theme(&parse_value::<String>(arg, &mut iter)?)?,
+            other => return Err(format!("unknown argument: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    tui::run(builder.build(), start, theme).map_err(|err| err.to_string())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("render") => render::run(&args[1..]),
+        Some("explore") => explore(&args[1..]),
+        _ => explore(&args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
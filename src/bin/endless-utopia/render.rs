@@ -0,0 +1,127 @@
+//! `endless-utopia render` - headless export of a world region
+//! Writes text, ANSI, HTML, SVG or a PPM/PNG biome map to stdout or a file
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use endless_utopia::export::{self, ExportFormat};
+use endless_utopia::world::WorldBuilder;
+use endless_utopia::{raster, Region, Theme, World};
+
+use crate::{load_theme, parse_world_option, parse_value};
+
+const USAGE: &str = "\
+Usage: endless-utopia render [OPTIONS]
+
+Options:
+  --x <X>             left tile coordinate (default 0)
+  --y <Y>             top tile coordinate (default 0)
+  --width <W>         width in tiles (default 80)
+  --height <H>        height in tiles (default 24)
+  --seed <N>          numeric world seed
+  --seed-str <S>      text world seed (hashed)
+  --glyphs <G>        characters to draw with: unicode, cp437, latin1 or ascii
+  --structures        add ruins, monoliths, rivers and roads
+  --format <F>        text, ansi, html, svg, ppm or png (default: from --output extension, else text)
+                      ppm/png write a biome map with one pixel per tile
+  --theme <T>         colours for every format: classic, amber, solarized,
+                      high-contrast, monochrome or a theme file (default classic)
+  -o, --output <FILE> write to FILE instead of stdout
+  -h, --help          show this help";
+
+/// What to write
+#[derive(Clone, Copy)]
+enum Format {
+    Export(ExportFormat),
+    Ppm,
+    Png,
+}
+
+fn parse_format(name: &str) -> Result<Format, String> {
+    match name.to_lowercase().as_str() {
+        "ppm" => Ok(Format::Ppm),
+        "png" => Ok(Format::Png),
+        other => other
+            .parse()
+            .map(Format::Export)
+            .map_err(|_| format!("unknown format: {} (expected text, ansi, html, svg, ppm or png)", other)),
+    }
+}
+
+fn write(
+    world: &World,
+    format: Format,
+    (x, y): (i64, i64),
+    (width, height): (usize, usize),
+    theme: &Theme,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match format {
+        Format::Export(format) => {
+            let mut region = Region::new(x, y, width, height);
+            world.fill_region(&mut region);
+            export::export(&region, format, theme, out)
+        }
+        Format::Ppm => raster::write_ppm(world, theme, x, y, width, height, out),
+        #[cfg(feature = "png")]
+        Format::Png => raster::write_png(world, theme, x, y, width, height, out),
+        #[cfg(not(feature = "png"))]
+        Format::Png => Err(io::Error::other("png output needs the `png` feature (cargo run --features cli,png)")),
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut builder = WorldBuilder::new();
+    let (mut x, mut y) = (0, 0);
+    let (mut width, mut height) = (80, 24);
+    let mut format = None;
+    let mut theme = Theme::default();
+    let mut output: Option<String> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if parse_world_option(arg, &mut iter, &mut builder)? {
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--x" => x = parse_value(arg, &mut iter)?,
+            "--y" => y = parse_value(arg, &mut iter)?,
+            "--width" => width = parse_value(arg, &mut iter)?,
+            "--height" => height = parse_value(arg, &mut iter)?,
+            "--format" => format = Some(parse_format(&parse_value::<String>(arg, &mut iter)?)?),
+            "--theme" => theme = load_theme(&parse_value::<String>(arg, &mut iter)?)?,
+            "--structures" => builder = builder.with_structures(),
+            "-o" | "--output" => output = Some(parse_value(arg, &mut iter)?),
+            other => return Err(format!("unknown argument: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    let world = builder.build();
+
+    // Guess the format from the file extension when not given
+    let format = format.unwrap_or_else(|| {
+        output
+            .as_deref()
+            .and_then(|path| path.rsplit_once('.'))
+            .and_then(|(_, ext)| parse_format(ext).ok())
+            .unwrap_or(Format::Export(ExportFormat::Text))
+    });
+
+    let result = match &output {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| format!("cannot create {}: {}", path, err))?;
+            let mut out = BufWriter::new(file);
+            write(&world, format, (x, y), (width, height), &theme, &mut out).and_then(|_| out.flush())
+        }
+        None => {
+            let mut out = io::stdout().lock();
+            write(&world, format, (x, y), (width, height), &theme, &mut out).and_then(|_| out.flush())
+        }
+    };
+
+    result.map_err(|err| err.to_string())
+}
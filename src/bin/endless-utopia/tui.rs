@@ -0,0 +1,342 @@
+//! Full-screen raw-mode terminal view of the world
+//! Mirrors the browser app: panning, a command prompt and a status line
+
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossterm::cursor;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+use endless_utopia::console::{Args, Command, CommandOutput, Context, Shell, ShellState, PROMPT};
+use endless_utopia::world::CAT_TICK_MILLIS;
+use endless_utopia::theme::Role;
+use endless_utopia::{Colour, Explorer, Region, Theme, Tile, World};
+
+/// Command output lines shown above the status line
+const OUTPUT_LINES: usize = 6;
+
+/// Tiles moved per key press (normal / with shift)
+const STEP: i64 = 1;
+const FAST_STEP: i64 = 10;
+
+/// Puts the terminal into raw mode on the alternate screen and restores it on drop,
+/// so a panic never leaves the user's shell unusable
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Run the explorer until the user quits
+pub fn run(world: World, start: (i64, i64), theme: Theme) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+    let mut tui = Tui::new(world, start, theme);
+
+    while !tui.quit {
+        tui.draw(&mut stdout)?;
+        // Redraw at least once per tick so the cat keeps walking
+        if !event::poll(Duration::from_millis(CAT_TICK_MILLIS))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => tui.on_key(key),
+            // Resize and anything else just trigger a redraw
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// `quit` / `exit`: only meaningful in the native explorer
+struct QuitCommand;
+
+impl Command for QuitCommand {
+    fn name(&self) -> &str {
+        "quit"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["exit"]
+    }
+
+    fn description(&self) -> &str {
+        "leave the explorer"
+    }
+
+    fn run(&self, _args: &Args, _state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        CommandOutput {
+            exit: true,
+            ..CommandOutput::default()
+        }
+    }
+}
+
+struct Tui {
+    explorer: Explorer,
+    shell: Shell,
+    /// Position (in tiles), cat, history, theme and rng shared with the commands
+    state: ShellState,
+    region: Region,
+    line_buffer: String,
+    input: Option<String>,
+    output: Vec<String>,
+    history_index: usize,
+    quit: bool,
+}
+
+impl Tui {
+    fn new(world: World, (x, y): (i64, i64), theme: Theme) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        let mut shell = Shell::new();
+        shell.register(Box::new(QuitCommand));
+
+        let mut state = ShellState::new(nanos);
+        state.x = x;
+        state.y = y;
+        // A theme loaded from a file can be switched back to with `theme`
+        if !state.themes.contains(&theme) {
+            state.themes.push(theme.clone());
+        }
+        state.theme = theme;
+
+        let mut tui = Tui {
+            explorer: Explorer::new(world),
+            shell,
+            state,
+            region: Region::new(0, 0, 0, 0),
+            line_buffer: String::new(),
+            input: None,
+            output: vec![
+                "EndlessUtopia Terminal".to_string(),
+                "Type 'help' for commands".to_string(),
+            ],
+            history_index: 0,
+            quit: false,
+        };
+        tui.update_cat();
+        tui
+    }
+
+    /// Sync the cat with the world's deterministic walk, like the browser does
+    fn update_cat(&mut self) {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.explorer.set_tick(millis / CAT_TICK_MILLIS);
+        let (cat_x, cat_y) = self.explorer.cat_position();
+        self.state.cat_x = cat_x;
+        self.state.cat_y = cat_y;
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+
+        if self.input.is_some() {
+            self.on_prompt_key(key);
+            return;
+        }
+
+        let fast = key.modifiers.contains(KeyModifiers::SHIFT);
+        let step = if fast { FAST_STEP } else { STEP };
+
+        match key.code {
+            KeyCode::Left | KeyCode::Char('a') => self.pan(-step, 0),
+            KeyCode::Right | KeyCode::Char('d') => self.pan(step, 0),
+            KeyCode::Up | KeyCode::Char('w') => self.pan(0, -step),
+            KeyCode::Down | KeyCode::Char('s') => self.pan(0, step),
+            // Shifted WASD arrive as upper case letters
+            KeyCode::Char('A') => self.pan(-FAST_STEP, 0),
+            KeyCode::Char('D') => self.pan(FAST_STEP, 0),
+            KeyCode::Char('W') => self.pan(0, -FAST_STEP),
+            KeyCode::Char('S') => self.pan(0, FAST_STEP),
+            KeyCode::Char(':') | KeyCode::Enter => {
+                self.input = Some(String::new());
+                self.history_index = self.state.history.len();
+            }
+            KeyCode::Char('c') => self.execute_command("cat"),
+            KeyCode::Char('r') => self.execute_command("random"),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn on_prompt_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Enter => {
+                let command = input.trim().to_string();
+                self.input = None;
+                if !command.is_empty() {
+                    self.execute_command(&command);
+                }
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Up if self.history_index > 0 => {
+                self.history_index -= 1;
+                *input = self.state.history[self.history_index].clone();
+            }
+            KeyCode::Down => {
+                let history = &self.state.history;
+                if self.history_index + 1 < history.len() {
+                    self.history_index += 1;
+                    *input = history[self.history_index].clone();
+                } else {
+                    self.history_index = history.len();
+                    input.clear();
+                }
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Run a command line through the shared shell
+    fn execute_command(&mut self, cmd: &str) {
+        self.update_cat();
+        let output = self.shell.execute(cmd, &mut self.state, self.explorer.world());
+        if output.clear {
+            self.output.clear();
+        }
+        self.output.extend(output.lines);
+        self.quit |= output.exit;
+    }
+
+    /// Current tile position
+    fn position(&self) -> (i64, i64) {
+        (self.state.x, self.state.y)
+    }
+
+    /// Pan by whole tiles, stopping at the edges of the world
+    fn pan(&mut self, dx: i64, dy: i64) {
+        self.state.x = self.state.x.saturating_add(dx);
+        self.state.y = self.state.y.saturating_add(dy);
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.update_cat();
+        let (cols, rows) = terminal::size()?;
+        let (cols, rows) = (cols as usize, rows as usize);
+
+        let output_lines = self.output.len().min(OUTPUT_LINES);
+        let prompt_lines = usize::from(self.input.is_some());
+        let view_height = rows.saturating_sub(1 + output_lines + prompt_lines);
+
+        // World view, centred on the current position
+        let (x, y) = self.position();
+        self.explorer.set_viewer(Some((x, y)));
+        let x_start = x.saturating_sub(cols as i64 / 2);
+        let y_start = y.saturating_sub(view_height as i64 / 2);
+        self.region.set_bounds(x_start, y_start, cols, view_height);
+        self.explorer.fill_region(&mut self.region);
+
+        queue!(out, Clear(ClearType::All))?;
+        let theme = &self.state.theme;
+        let style = |tile: &Tile| (theme.tile_colour(tile), tile.bg, tile.intensity);
+        for row in 0..self.region.height() {
+            queue!(out, cursor::MoveTo(0, row as u16))?;
+
+            // One colour change per run of equally styled tiles
+            let tiles = self.region.row(row);
+            let mut start = 0;
+            while start < tiles.len() {
+                let first = tiles[start];
+                let end = tiles[start..].iter().position(|tile| style(tile) != style(&first)).map_or(tiles.len(), |len| start + len);
+                self.line_buffer.clear();
+                self.line_buffer.extend(tiles[start..end].iter().map(|tile| tile.character));
+
+                let attribute = match first.intensity {
+                    0..=127 => Attribute::Dim,
+                    u8::MAX => Attribute::Bold,
+                    _ => Attribute::NormalIntensity,
+                };
+                queue!(out, SetAttribute(Attribute::Reset), SetForegroundColor(terminal_colour(theme.tile_colour(&first))), SetAttribute(attribute))?;
+                if let Some(bg) = first.bg {
+                    queue!(out, SetBackgroundColor(terminal_colour(bg)))?;
+                }
+                queue!(out, Print(&self.line_buffer))?;
+                start = end;
+            }
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+
+        // Command output and prompt
+        let mut line = view_height;
+        queue!(out, SetForegroundColor(terminal_colour(theme.ui(Role::Terminal))))?;
+        for text in &self.output[self.output.len() - output_lines..] {
+            queue!(out, cursor::MoveTo(0, line as u16), Print(truncate(text, cols)))?;
+            line += 1;
+        }
+        if let Some(input) = &self.input {
+            let prompt = format!("{}{}█", PROMPT, input);
+            // Keep the end of long input visible
+            let skip = prompt.chars().count().saturating_sub(cols);
+            let visible: String = prompt.chars().skip(skip).collect();
+            queue!(out, cursor::MoveTo(0, line as u16), Print(visible))?;
+            line += 1;
+        }
+
+        // Status line
+        let biome = self.explorer.world().get_tile_at(x, y, self.explorer.tick()).biome;
+        let status = format!(
+            " X: {}  Y: {}  {:?}  seed {}  |  arrows/WASD move  : command  q quit",
+            x,
+            y,
+            biome,
+            self.explorer.world().seed()
+        );
+        queue!(
+            out,
+            cursor::MoveTo(0, line as u16),
+            SetAttribute(Attribute::Reverse),
+            SetForegroundColor(terminal_colour(self.state.theme.ui(Role::World))),
+            Print(format!("{:<width$}", truncate(&status, cols), width = cols)),
+            SetAttribute(Attribute::Reset),
+            ResetColor,
+        )?;
+
+        out.flush()
+    }
+}
+
+/// Crossterm colour for a tile colour (palette entries keep the terminal's own palette)
+fn terminal_colour(colour: Colour) -> Color {
+    match colour {
+        Colour::Palette(index) => Color::AnsiValue(index % 16),
+        Colour::Rgb(r, g, b) => Color::Rgb { r, g, b },
+    }
+}
+
+/// Cut a line to at most `width` characters
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
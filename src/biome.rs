@@ -0,0 +1,352 @@
+//! Pluggable biome generators for EndlessUtopia
+//! Built-in biomes (calm, pattern, glitch) and the registry `World` picks them from
+
+use crate::noise::{tile_coord, NoiseParams};
+use crate::world::{Biome, PatternKind, Tile};
+
+/// How `World` decides which biome a tile belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BiomeMode {
+    /// Every tile picks its biome independently from its hash (the original world)
+    #[default]
+    PerTile,
+    /// Biomes follow low-frequency noise, forming contiguous regions
+    Regions(NoiseParams),
+}
+
+
+/// A biome that can be registered with a `World` (see `World::builder`)
+pub trait BiomeGenerator: Send + Sync {
+    /// Short, unique name, e.g. "forest" - registering a second generator
+    /// with the same name replaces the first one
+    fn name(&self) -> &str;
+
+    /// Human readable description
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Biome reported on tiles produced by this generator
+    fn biome(&self) -> Biome;
+
+    /// Default share of the world, relative to the other registered biomes
+    fn weight(&self) -> u32;
+
+    /// Character for the tile at (x, y); `hash` is the seeded coordinate hash
+    fn char_at(&self, x: i64, y: i64, hash: u64) -> char;
+
+    /// The whole tile at (x, y). Override to set a `PatternKind` or colours;
+    /// by default it is `char_at` in the biome's colour.
+    fn tile_at(&self, x: i64, y: i64, hash: u64) -> Tile {
+        Tile::new(self.char_at(x, y, hash), self.biome())
+    }
+}
+
+/// Empty peaceful spaces
+pub struct CalmBiome;
+
+impl BiomeGenerator for CalmBiome {
+    fn name(&self) -> &str {
+        "calm"
+    }
+
+    fn description(&self) -> &str {
+        "Empty peaceful spaces"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::Calm
+    }
+
+    fn weight(&self) -> u32 {
+        60
+    }
+
+    fn char_at(&self, _x: i64, _y: i64, _hash: u64) -> char {
+        ' '
+    }
+}
+
+/// Regular patterns: checkerboards, waves, stripes, rings...
+pub struct PatternBiome;
+
+impl BiomeGenerator for PatternBiome {
+    fn name(&self) -> &str {
+        "pattern"
+    }
+
+    fn description(&self) -> &str {
+        "Waves, checkerboards, stripes and rings"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::Pattern
+    }
+
+    fn weight(&self) -> u32 {
+        25
+    }
+
+    fn char_at(&self, x: i64, y: i64, hash: u64) -> char {
+        self.tile_at(x, y, hash).character
+    }
+
+    fn tile_at(&self, x: i64, y: i64, hash: u64) -> Tile {
+        let pattern_type = (hash / 100) % 10;
+
+        let (pattern, character) = match pattern_type {
+            0 | 1 => {
+                // Checkerboard-like patterns
+                (PatternKind::Checkerboard, if x.wrapping_add(y) % 2 == 0 { '·' } else { ' ' })
+            }
+            2 | 3 => {
+                // Wave patterns
+                let wave = ((tile_coord(x) * 0.5).sin() + (tile_coord(y) * 0.3).cos()) * 3.0;
+                (PatternKind::Wave, if wave.abs() < 1.0 { '~' } else { ' ' })
+            }
+            4 => {
+                // Diagonal stripes
+                (PatternKind::Diagonal, if (x as i128 - y as i128) % 3 == 0 { '/' } else { ' ' })
+            }
+            5 => {
+                // Sparse dots
+                (PatternKind::Dots, if (x as i128 * 7 + y as i128 * 11) % 13 == 0 { '•' } else { ' ' })
+            }
+            6 => {
+                // Cross patterns
+                (PatternKind::Cross, if x % 5 == 0 || y % 5 == 0 { '+' } else { ' ' })
+            }
+            7 => {
+                // Concentric patterns (exact integer distance, even at the edges of the world)
+                let (dx, dy) = (x.unsigned_abs() as u128, y.unsigned_abs() as u128);
+                let dist = (dx * dx + dy * dy).isqrt();
+                (PatternKind::Rings, if dist % 10 == 0 { 'o' } else { ' ' })
+            }
+            8 => {
+                // Random-looking sparse characters
+                let chars = ['*', '·', '˙', ' ', ' ', ' '];
+                (PatternKind::Scatter, chars[(hash % chars.len() as u64) as usize])
+            }
+            _ => {
+                // Minimalist single dots
+                (PatternKind::Sparse, if hash.is_multiple_of(20) { '.' } else { ' ' })
+            }
+        };
+        Tile::new(character, self.biome()).with_pattern(pattern)
+    }
+}
+
+/// Glitchy/corrupted areas made of block characters
+pub struct GlitchBiome;
+
+impl BiomeGenerator for GlitchBiome {
+    fn name(&self) -> &str {
+        "glitch"
+    }
+
+    fn description(&self) -> &str {
+        "Corrupted areas with block characters"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::Glitch
+    }
+
+    fn weight(&self) -> u32 {
+        15
+    }
+
+    fn char_at(&self, x: i64, y: i64, hash: u64) -> char {
+        self.tile_at(x, y, hash).character
+    }
+
+    fn tile_at(&self, _x: i64, _y: i64, hash: u64) -> Tile {
+        let glitch_intensity = hash % 10;
+
+        let (pattern, chars): (_, &[char]) = if glitch_intensity < 3 {
+            // Light glitches
+            (PatternKind::GlitchLight, &['▓', '▒', '░', '█'])
+        } else if glitch_intensity < 6 {
+            // Medium glitches with symbols
+            (PatternKind::GlitchMedium, &['#', '$', '%', '&', '@', '¤'])
+        } else if glitch_intensity < 8 {
+            // Heavy glitches
+            (PatternKind::GlitchHeavy, &['█', '▓', '▒', '░', '▪', '▫'])
+        } else {
+            // Rare intense glitches
+            (PatternKind::GlitchIntense, &['▀', '▄', '▌', '▐', '█', '▓'])
+        };
+        let character = chars[(hash % chars.len() as u64) as usize];
+        Tile::new(character, self.biome()).with_pattern(pattern)
+    }
+}
+
+/// Weighted set of biome generators
+pub struct BiomeRegistry {
+    entries: Vec<(Box<dyn BiomeGenerator>, u32)>,
+    total_weight: u64,
+}
+
+impl BiomeRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        BiomeRegistry {
+            entries: Vec::new(),
+            total_weight: 0,
+        }
+    }
+
+    /// The built-in calm/pattern/glitch split (60/25/15)
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(CalmBiome));
+        registry.register(Box::new(PatternBiome));
+        registry.register(Box::new(GlitchBiome));
+        registry
+    }
+
+    /// Register a generator with its own default weight
+    pub fn register(&mut self, generator: Box<dyn BiomeGenerator>) {
+        let weight = generator.weight();
+        self.register_weighted(generator, weight);
+    }
+
+    /// Register a generator with an explicit weight.
+    /// A generator with the same name is replaced in place.
+    pub fn register_weighted(&mut self, generator: Box<dyn BiomeGenerator>, weight: u32) {
+        match self.entries.iter_mut().find(|(g, _)| g.name() == generator.name()) {
+            Some(entry) => *entry = (generator, weight),
+            None => self.entries.push((generator, weight)),
+        }
+        self.total_weight = self.entries.iter().map(|(_, w)| *w as u64).sum();
+    }
+
+    /// Remove every registered generator
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.total_weight = 0;
+    }
+
+    /// Pick the generator for a coordinate hash
+    pub fn select(&self, hash: u64) -> Option<&dyn BiomeGenerator> {
+        if self.total_weight == 0 {
+            return None;
+        }
+
+        let mut selector = hash % self.total_weight;
+        for (generator, weight) in &self.entries {
+            let weight = *weight as u64;
+            if selector < weight {
+                return Some(generator.as_ref());
+            }
+            selector -= weight;
+        }
+
+        None
+    }
+
+    /// Registered generators with their weights, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = (&dyn BiomeGenerator, u32)> {
+        self.entries.iter().map(|(g, w)| (g.as_ref(), *w))
+    }
+
+    /// Sum of all weights
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+}
+
+impl Default for BiomeRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::Colour;
+
+    struct ForestBiome;
+
+    impl BiomeGenerator for ForestBiome {
+        fn name(&self) -> &str {
+            "forest"
+        }
+
+        fn biome(&self) -> Biome {
+            Biome::Custom("forest")
+        }
+
+        fn weight(&self) -> u32 {
+            10
+        }
+
+        fn char_at(&self, _x: i64, _y: i64, _hash: u64) -> char {
+            '♣'
+        }
+    }
+
+    #[test]
+    fn test_default_split() {
+        let registry = BiomeRegistry::with_defaults();
+
+        assert_eq!(registry.total_weight(), 100);
+        assert_eq!(registry.select(59).unwrap().biome(), Biome::Calm);
+        assert_eq!(registry.select(60).unwrap().biome(), Biome::Pattern);
+        assert_eq!(registry.select(84).unwrap().biome(), Biome::Pattern);
+        assert_eq!(registry.select(85).unwrap().biome(), Biome::Glitch);
+        assert_eq!(registry.select(199).unwrap().biome(), Biome::Glitch);
+    }
+
+    #[test]
+    fn test_register_replaces_by_name() {
+        let mut registry = BiomeRegistry::with_defaults();
+        registry.register(Box::new(ForestBiome));
+        registry.register_weighted(Box::new(ForestBiome), 40);
+
+        assert_eq!(registry.iter().count(), 4);
+        assert_eq!(registry.total_weight(), 140);
+        assert_eq!(registry.select(100).unwrap().name(), "forest");
+    }
+
+    #[test]
+    fn test_tiles_record_their_pattern() {
+        let rings = PatternBiome.tile_at(30, 40, 700);
+        assert_eq!((rings.character, rings.pattern, rings.fg), ('o', PatternKind::Rings, Colour::Palette(3)));
+
+        let glitch = GlitchBiome.tile_at(0, 0, 9);
+        assert_eq!((glitch.character, glitch.pattern, glitch.intensity), ('▐', PatternKind::GlitchIntense, u8::MAX));
+
+        // Biomes without sub-patterns get plain tiles in their biome's colour
+        let forest = ForestBiome.tile_at(0, 0, 0);
+        assert_eq!((forest.pattern, forest.fg), (PatternKind::Plain, Biome::Custom("forest").colour()));
+
+        for hash in 0..2000 {
+            assert_eq!(PatternBiome.char_at(hash as i64, 3, hash), PatternBiome.tile_at(hash as i64, 3, hash).character);
+            assert_eq!(GlitchBiome.char_at(0, 0, hash), GlitchBiome.tile_at(0, 0, hash).character);
+        }
+    }
+
+    #[test]
+    fn test_patterns_near_world_edges() {
+        // hash 700 selects the concentric rings
+        let rings = |x: i64, y: i64| PatternBiome.char_at(x, y, 700);
+        assert_eq!(rings(30, -40), 'o');
+        assert_eq!(rings(6_000_000_000_000_000_000, 8_000_000_000_000_000_000), 'o');
+        assert_eq!(rings(6_000_000_000_000_000_005, 8_000_000_000_000_000_000), ' ');
+
+        for hash in (0..1000).step_by(100) {
+            PatternBiome.char_at(i64::MIN, i64::MAX, hash);
+            PatternBiome.char_at(i64::MAX, i64::MIN, hash);
+        }
+    }
+
+    #[test]
+    fn test_empty_registry() {
+        let mut registry = BiomeRegistry::with_defaults();
+        registry.clear();
+
+        assert!(registry.select(42).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+//! Chunked tile cache for EndlessUtopia
+//! Fixed-size chunks of generated tiles, kept in an LRU cache with a memory budget
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::world::Tile;
+
+/// Width and height of a chunk in tiles
+pub const CHUNK_SIZE: usize = 32;
+
+/// Default cache budget (16 MiB, about 400 chunks): twice the ~200 chunks a full-screen
+/// 500x300-tile view spans, so panning never evicts chunks that are still visible
+pub const DEFAULT_CACHE_BUDGET: usize = 16 * 1024 * 1024;
+
+/// Position of a chunk in chunk units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkCoord {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl ChunkCoord {
+    /// The chunk containing tile (x, y)
+    pub fn containing(x: i64, y: i64) -> Self {
+        ChunkCoord {
+            x: x.div_euclid(CHUNK_SIZE as i64),
+            y: y.div_euclid(CHUNK_SIZE as i64),
+        }
+    }
+
+    /// Tile coordinates of the chunk's top-left corner
+    pub fn origin(&self) -> (i64, i64) {
+        (self.x.wrapping_mul(CHUNK_SIZE as i64), self.y.wrapping_mul(CHUNK_SIZE as i64))
+    }
+}
+
+/// A generated CHUNK_SIZE x CHUNK_SIZE block of tiles (row-major)
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    coord: ChunkCoord,
+    tiles: Vec<Tile>,
+}
+
+impl Chunk {
+    /// Generate a chunk by calling `generate` with the world coordinates of every tile
+    pub fn generate(coord: ChunkCoord, mut generate: impl FnMut(i64, i64) -> Tile) -> Self {
+        let (ox, oy) = coord.origin();
+        let mut tiles = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE);
+        for y in 0..CHUNK_SIZE as i64 {
+            for x in 0..CHUNK_SIZE as i64 {
+                tiles.push(generate(ox.wrapping_add(x), oy.wrapping_add(y)));
+            }
+        }
+        Chunk { coord, tiles }
+    }
+
+    pub fn coord(&self) -> ChunkCoord {
+        self.coord
+    }
+
+    /// Tile at chunk-local coordinates
+    pub fn tile(&self, local_x: usize, local_y: usize) -> &Tile {
+        &self.tiles[local_y * CHUNK_SIZE + local_x]
+    }
+
+    /// One row of the chunk
+    pub fn row(&self, local_y: usize) -> &[Tile] {
+        &self.tiles[local_y * CHUNK_SIZE..(local_y + 1) * CHUNK_SIZE]
+    }
+
+    /// Approximate heap size of one chunk in bytes
+    pub fn size_in_bytes() -> usize {
+        CHUNK_SIZE * CHUNK_SIZE * std::mem::size_of::<Tile>()
+    }
+}
+
+/// Cache counters, mainly for tuning the budget
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub chunks: usize,
+}
+
+/// Least-recently-used cache of generated chunks.
+/// Every use pushes the chunk and a fresh stamp onto `order`; entries whose stamp no longer
+/// matches the chunk's are stale and skipped, so touching and evicting are amortised O(1).
+pub struct ChunkCache {
+    chunks: HashMap<ChunkCoord, (Chunk, u64)>,
+    order: VecDeque<(ChunkCoord, u64)>,
+    capacity: usize,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl ChunkCache {
+    /// Create a cache holding as many chunks as fit in `budget_bytes` (at least one)
+    pub fn with_budget(budget_bytes: usize) -> Self {
+        ChunkCache {
+            chunks: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: Self::capacity_for(budget_bytes),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Change the memory budget, evicting chunks if needed
+    pub fn set_budget(&mut self, budget_bytes: usize) {
+        self.capacity = Self::capacity_for(budget_bytes);
+        while self.chunks.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// Maximum number of chunks kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the cached chunk, generating it with `generate` on a miss
+    pub fn get_or_insert_with(&mut self, coord: ChunkCoord, generate: impl FnOnce(ChunkCoord) -> Chunk) -> &Chunk {
+        if self.get(coord).is_none() {
+            self.insert(generate(coord));
+        }
+        &self.chunks[&coord].0
+    }
+
+    /// The cached chunk, counting a hit or a miss
+    pub fn get(&mut self, coord: ChunkCoord) -> Option<&Chunk> {
+        if !self.chunks.contains_key(&coord) {
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.touch(coord);
+        self.chunks.get(&coord).map(|(chunk, _)| chunk)
+    }
+
+    /// Cache a chunk generated after a miss, evicting the least recently used if full.
+    /// A chunk that is already cached (e.g. generated by another thread meanwhile) is kept.
+    pub fn insert(&mut self, chunk: Chunk) {
+        let coord = chunk.coord;
+        if !self.chunks.contains_key(&coord) {
+            if self.chunks.len() >= self.capacity {
+                self.evict_oldest();
+            }
+            self.chunks.insert(coord, (chunk, 0));
+        }
+        self.touch(coord);
+    }
+
+    /// Whether a chunk is currently cached (does not touch its LRU position)
+    pub fn contains(&self, coord: ChunkCoord) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    /// Drop every cached chunk
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.order.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            chunks: self.chunks.len(),
+            ..self.stats
+        }
+    }
+
+    /// Make a cached chunk the most recently used
+    fn touch(&mut self, coord: ChunkCoord) {
+        self.clock += 1;
+        if let Some(entry) = self.chunks.get_mut(&coord) {
+            entry.1 = self.clock;
+            self.order.push_back((coord, self.clock));
+        }
+        if self.order.len() > 2 * self.capacity {
+            self.compact();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((coord, stamp)) = self.order.pop_front() {
+            if self.chunks.get(&coord).is_some_and(|(_, last_used)| *last_used == stamp) {
+                self.chunks.remove(&coord);
+                self.stats.evictions += 1;
+                return;
+            }
+        }
+    }
+
+    /// Drop the stale entries of `order`
+    fn compact(&mut self) {
+        let chunks = &self.chunks;
+        self.order.retain(|(coord, stamp)| chunks.get(coord).is_some_and(|(_, last_used)| last_used == stamp));
+    }
+
+    fn capacity_for(budget_bytes: usize) -> usize {
+        (budget_bytes / Chunk::size_in_bytes()).max(1)
+    }
+}
+
+impl Default for ChunkCache {
+    fn default() -> Self {
+        Self::with_budget(DEFAULT_CACHE_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::Rect;
+    use crate::world::Biome;
+
+    fn calm_chunk(coord: ChunkCoord) -> Chunk {
+        Chunk::generate(coord, |_, _| Tile::new(' ', Biome::Calm))
+    }
+
+    #[test]
+    fn test_chunk_coord_negative() {
+        assert_eq!(ChunkCoord::containing(0, 31), ChunkCoord { x: 0, y: 0 });
+        assert_eq!(ChunkCoord::containing(-1, 32), ChunkCoord { x: -1, y: 1 });
+        assert_eq!(ChunkCoord::containing(-33, -32).origin(), (-64, -32));
+
+        // Chunks at the edges of the world still hold their own tiles
+        let last = ChunkCoord::containing(i64::MAX, i64::MIN);
+        assert_eq!(last.origin(), (i64::MAX - 31, i64::MIN));
+        let chunk = Chunk::generate(last, |x, y| {
+            Tile::new(if (x, y) == (i64::MAX, i64::MIN) { '#' } else { ' ' }, Biome::Calm)
+        });
+        assert_eq!(chunk.tile(CHUNK_SIZE - 1, 0).character, '#');
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = ChunkCache::with_budget(Chunk::size_in_bytes() * 2);
+        let a = ChunkCoord { x: 0, y: 0 };
+        let b = ChunkCoord { x: 1, y: 0 };
+        let c = ChunkCoord { x: 2, y: 0 };
+
+        cache.get_or_insert_with(a, calm_chunk);
+        cache.get_or_insert_with(b, calm_chunk);
+        // Touch `a` so `b` becomes the least recently used
+        cache.get_or_insert_with(a, calm_chunk);
+        cache.get_or_insert_with(c, calm_chunk);
+
+        assert!(cache.contains(a));
+        assert!(!cache.contains(b));
+        assert!(cache.contains(c));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions, stats.chunks), (1, 3, 1, 2));
+    }
+
+    #[test]
+    fn test_get_then_insert() {
+        let mut cache = ChunkCache::with_budget(Chunk::size_in_bytes() * 4);
+        let coord = ChunkCoord { x: -3, y: 7 };
+        assert!(cache.get(coord).is_none());
+
+        // Two threads may both generate a missing chunk; the first one cached wins
+        cache.insert(Chunk::generate(coord, |_, _| Tile::new('a', Biome::Calm)));
+        cache.insert(Chunk::generate(coord, |_, _| Tile::new('b', Biome::Calm)));
+        assert_eq!(cache.get(coord).unwrap().tile(0, 0).character, 'a');
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.chunks), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_full_screen_view_survives_panning() {
+        // Touch chunks in the order `World::fill_region` does: tile row by tile row
+        fn draw(cache: &mut ChunkCache, view: Rect) -> Vec<ChunkCoord> {
+            let mut seen = Vec::new();
+            for y in view.y..view.y + view.height as i64 {
+                let mut x = view.x;
+                while x < view.x + view.width as i64 {
+                    let coord = ChunkCoord::containing(x, y);
+                    cache.get_or_insert_with(coord, calm_chunk);
+                    if !seen.contains(&coord) {
+                        seen.push(coord);
+                    }
+                    x = coord.origin().0 + CHUNK_SIZE as i64;
+                }
+            }
+            seen
+        }
+
+        // A 500x300-tile view plus its margin, straddling as many chunks as it can
+        let mut cache = ChunkCache::default();
+        let mut visible = draw(&mut cache, Rect::new(-17, -9, 504, 304));
+        assert_eq!(visible.len(), 17 * 11);
+        for step in 1..=20 {
+            // Scroll up and left by a chunk, the worst case for least-recently-used order
+            let misses = cache.stats().misses;
+            let next = draw(&mut cache, Rect::new(-17 - step * 32, -9 - step * 32, 504, 304));
+            // Only chunks scrolling into view are generated
+            let fresh = next.iter().filter(|coord| !visible.contains(coord)).count() as u64;
+            assert_eq!(cache.stats().misses - misses, fresh, "step {}", step);
+            assert!(next.iter().all(|&coord| cache.contains(coord)));
+            visible = next;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Tile colours for EndlessUtopia
+//! Palette indices (the 16 classic terminal colours) or exact RGB, shared by all renderers
+
+use std::fmt;
+use std::str::FromStr;
+
+/// RGB of the 16 palette entries, in terminal order (VGA-style defaults)
+pub const PALETTE: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00], // 0 black
+    [0xaa, 0x00, 0x00], // 1 red
+    [0x00, 0xaa, 0x00], // 2 green
+    [0xaa, 0x55, 0x00], // 3 yellow (brown)
+    [0x00, 0x00, 0xaa], // 4 blue
+    [0xaa, 0x00, 0xaa], // 5 magenta
+    [0x00, 0xaa, 0xaa], // 6 cyan
+    [0xaa, 0xaa, 0xaa], // 7 white
+    [0x55, 0x55, 0x55], // 8 bright black (grey)
+    [0xff, 0x55, 0x55], // 9 bright red
+    [0x55, 0xff, 0x55], // 10 bright green
+    [0xff, 0xff, 0x55], // 11 bright yellow
+    [0x55, 0x55, 0xff], // 12 bright blue
+    [0xff, 0x55, 0xff], // 13 bright magenta
+    [0x55, 0xff, 0xff], // 14 bright cyan
+    [0xff, 0xff, 0xff], // 15 bright white
+];
+
+/// A tile colour: an entry of the 16-colour terminal palette, or exact RGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colour {
+    /// Index into `PALETTE` (taken modulo 16)
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Colour {
+    pub const GREEN: Colour = Colour::Palette(2);
+    pub const GREY: Colour = Colour::Palette(8);
+    pub const BRIGHT_GREEN: Colour = Colour::Palette(10);
+    pub const BRIGHT_YELLOW: Colour = Colour::Palette(11);
+    pub const BRIGHT_MAGENTA: Colour = Colour::Palette(13);
+    pub const BRIGHT_CYAN: Colour = Colour::Palette(14);
+    pub const BRIGHT_WHITE: Colour = Colour::Palette(15);
+
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Colour::Palette(index) => PALETTE[index as usize % PALETTE.len()],
+            Colour::Rgb(r, g, b) => [r, g, b],
+        }
+    }
+
+    /// SGR parameters selecting this colour as the foreground (`background` for the back)
+    pub fn ansi(self, background: bool) -> String {
+        let base = if background { 40 } else { 30 };
+        match self {
+            Colour::Palette(index) if index % 16 < 8 => (base + index % 16).to_string(),
+            Colour::Palette(index) => (base + 60 + index % 16 - 8).to_string(),
+            Colour::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// Parses a palette index (`0` to `15`) or CSS hex notation (`#5f5` or `#55ff55`)
+impl FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!("invalid colour '{}' (expected 0-15, #rgb or #rrggbb)", s);
+        let Some(hex) = s.strip_prefix('#') else {
+            return match s.parse::<u8>() {
+                Ok(index) if (index as usize) < PALETTE.len() => Ok(Colour::Palette(index)),
+                _ => Err(invalid()),
+            };
+        };
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+        match hex.len() {
+            // #rgb repeats each digit: #5f5 is #55ff55
+            3 => Ok(Colour::Rgb(
+                channel(&hex[0..1])? * 17,
+                channel(&hex[1..2])? * 17,
+                channel(&hex[2..3])? * 17,
+            )),
+            6 => Ok(Colour::Rgb(channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// CSS hex notation, e.g. `#55ff55`
+impl fmt::Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.rgb();
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_and_rgb() {
+        assert_eq!(Colour::BRIGHT_GREEN.rgb(), [0x55, 0xff, 0x55]);
+        assert_eq!(Colour::Palette(18).rgb(), Colour::GREEN.rgb());
+        assert_eq!(Colour::Rgb(1, 2, 3).rgb(), [1, 2, 3]);
+        assert_eq!(Colour::BRIGHT_MAGENTA.to_string(), "#ff55ff");
+        assert_eq!(Colour::Rgb(255, 0, 16).to_string(), "#ff0010");
+    }
+
+    #[test]
+    fn test_ansi_codes() {
+        assert_eq!(Colour::GREEN.ansi(false), "32");
+        assert_eq!(Colour::BRIGHT_YELLOW.ansi(false), "93");
+        assert_eq!(Colour::GREY.ansi(true), "100");
+        assert_eq!(Colour::Rgb(1, 2, 3).ansi(false), "38;2;1;2;3");
+        assert_eq!(Colour::Rgb(1, 2, 3).ansi(true), "48;2;1;2;3");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("13".parse(), Ok(Colour::BRIGHT_MAGENTA));
+        assert_eq!("#ffaa00".parse(), Ok(Colour::Rgb(255, 170, 0)));
+        assert_eq!(" #1a1 ".parse(), Ok(Colour::Rgb(0x11, 0xaa, 0x11)));
+        assert_eq!(Colour::Rgb(9, 8, 7).to_string().parse(), Ok(Colour::Rgb(9, 8, 7)));
+        for bad in ["16", "-1", "#12", "#ggg", "#+12345", "green", ""] {
+            assert!(bad.parse::<Colour>().is_err(), "{}", bad);
+        }
+    }
+}
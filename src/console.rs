@@ -0,0 +1,651 @@
+//! Platform-independent terminal commands for EndlessUtopia
+//! The browser terminal and the native explorer both drive a `Shell`
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::region::Rect;
+use crate::theme::Theme;
+use crate::world::{World, LANDMARK_SEARCH_RANGE};
+
+/// Prompt shown before every command
+pub const PROMPT: &str = "explorer@endlessutopia:~$ ";
+
+/// `random` warps to a position within +-RANDOM_RANGE of the origin
+pub const RANDOM_RANGE: i64 = 2000;
+
+/// `landmarks` lists up to this many landmarks within LANDMARKS_RADIUS tiles
+pub const LANDMARKS_SHOWN: usize = 8;
+pub const LANDMARKS_RADIUS: u32 = 256;
+
+/// State the commands read and change; frontends render from it
+#[derive(Debug, Clone)]
+pub struct ShellState {
+    /// Tile at the viewport centre (see `Viewport::centre_tile`)
+    pub x: i64,
+    pub y: i64,
+    /// Tile the cat is on
+    pub cat_x: i64,
+    pub cat_y: i64,
+    /// Every command entered, oldest first
+    pub history: Vec<String>,
+    /// Colours the frontend renders with
+    pub theme: Theme,
+    /// Themes `theme` can switch to: the built-in ones plus any the frontend loaded
+    pub themes: Vec<Theme>,
+    rng: u64,
+}
+
+impl ShellState {
+    /// `rng_seed` drives the `random` command; pass something time-based for variety
+    pub fn new(rng_seed: u64) -> Self {
+        ShellState {
+            x: 0,
+            y: 0,
+            cat_x: 0,
+            cat_y: 0,
+            history: Vec::new(),
+            theme: Theme::default(),
+            themes: Theme::builtin(),
+            // xorshift must never start at zero
+            rng: rng_seed | 1,
+        }
+    }
+
+    /// Next pseudo-random number in [0, 1) (xorshift64)
+    pub fn random(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// What a command produced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandOutput {
+    /// Lines to append to the terminal
+    pub lines: Vec<String>,
+    /// Clear the terminal before appending `lines`
+    pub clear: bool,
+    /// The frontend should close (if it supports that)
+    pub exit: bool,
+}
+
+impl CommandOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output consisting of a single line
+    pub fn line(text: impl Into<String>) -> Self {
+        CommandOutput {
+            lines: vec![text.into()],
+            ..Self::default()
+        }
+    }
+
+    /// A single "error: ..." line
+    pub fn error(message: impl fmt::Display) -> Self {
+        Self::line(format!("error: {}", message))
+    }
+
+    /// Append a line
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.lines.push(text.into());
+    }
+}
+
+/// A parsed command line: the command name and its arguments.
+/// Arguments are split on whitespace; double quotes group words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    name: String,
+    args: Vec<String>,
+}
+
+impl Args {
+    /// Parse a command line; `None` if it contains no command
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        let mut in_word = false;
+        let mut quoted = false;
+
+        for c in input.chars() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    in_word = true;
+                }
+                c if c.is_whitespace() && !quoted => {
+                    if in_word {
+                        words.push(std::mem::take(&mut current));
+                        in_word = false;
+                    }
+                }
+                c => {
+                    current.push(c);
+                    in_word = true;
+                }
+            }
+        }
+        if in_word {
+            words.push(current);
+        }
+
+        let mut words = words.into_iter();
+        let name = words.next()?.to_lowercase();
+        Some(Args {
+            name,
+            args: words.collect(),
+        })
+    }
+
+    /// Command name, lower-cased
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of arguments (excluding the name)
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Argument `index` (0 = first argument after the name)
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    /// Parse argument `index`
+    pub fn parse_arg<T: FromStr>(&self, index: usize) -> Option<Result<T, T::Err>> {
+        self.get(index).map(str::parse)
+    }
+
+    /// Arguments from `index` on, joined by single spaces
+    pub fn rest(&self, index: usize) -> String {
+        self.args.get(index..).map(|rest| rest.join(" ")).unwrap_or_default()
+    }
+}
+
+/// What a command can see while running
+pub struct Context<'a> {
+    pub world: &'a World,
+    pub shell: &'a Shell,
+}
+
+/// A terminal command
+pub trait Command {
+    /// Name typed by the user
+    fn name(&self) -> &str;
+
+    /// Alternative names
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Usage shown by `help`, e.g. "goto X Y"
+    fn usage(&self) -> &str {
+        self.name()
+    }
+
+    /// One-line description shown by `help`
+    fn description(&self) -> &str;
+
+    fn run(&self, args: &Args, state: &mut ShellState, ctx: &Context) -> CommandOutput;
+}
+
+/// Registry of commands
+pub struct Shell {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Shell {
+    /// A shell with the built-in commands (help, clear, goto, cat, random, pos, landmarks, theme)
+    pub fn new() -> Self {
+        let mut shell = Self::empty();
+        shell.register(Box::new(HelpCommand));
+        shell.register(Box::new(ClearCommand));
+        shell.register(Box::new(GotoCommand));
+        shell.register(Box::new(CatCommand));
+        shell.register(Box::new(RandomCommand));
+        shell.register(Box::new(PosCommand));
+        shell.register(Box::new(LandmarksCommand));
+        shell.register(Box::new(ThemeCommand));
+        shell
+    }
+
+    /// A shell without any commands
+    pub fn empty() -> Self {
+        Shell { commands: Vec::new() }
+    }
+
+    /// Add a command; a command with the same name is replaced
+    pub fn register(&mut self, command: Box<dyn Command>) {
+        match self.commands.iter_mut().find(|c| c.name() == command.name()) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    /// Registered commands, in registration order
+    pub fn commands(&self) -> impl Iterator<Item = &dyn Command> {
+        self.commands.iter().map(|c| c.as_ref())
+    }
+
+    /// Look a command up by name or alias
+    pub fn find(&self, name: &str) -> Option<&dyn Command> {
+        self.commands()
+            .find(|c| c.name() == name || c.aliases().contains(&name))
+    }
+
+    /// Run one line of input. The output starts with the echoed prompt line
+    /// (unless the command cleared the terminal).
+    pub fn execute(&self, input: &str, state: &mut ShellState, world: &World) -> CommandOutput {
+        let input = input.trim();
+        state.history.push(input.to_string());
+
+        let echo = format!("{}{}", PROMPT, input);
+        let Some(args) = Args::parse(input) else {
+            return CommandOutput::line(echo);
+        };
+
+        let mut output = match self.find(args.name()) {
+            Some(command) => command.run(&args, state, &Context { world, shell: self }),
+            None => CommandOutput {
+                lines: vec![
+                    format!("command not found: {}", args.name()),
+                    "type 'help' for available commands".to_string(),
+                ],
+                ..CommandOutput::default()
+            },
+        };
+
+        if !output.clear {
+            output.lines.insert(0, echo);
+        }
+        output
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct HelpCommand;
+
+impl Command for HelpCommand {
+    fn name(&self) -> &str {
+        "help"
+    }
+
+    fn description(&self) -> &str {
+        "show this help"
+    }
+
+    fn run(&self, _args: &Args, _state: &mut ShellState, ctx: &Context) -> CommandOutput {
+        let mut output = CommandOutput::line("Commands:");
+        for command in ctx.shell.commands() {
+            output.push(format!("  {:<11} {}", command.usage(), command.description()));
+        }
+        output.push("");
+        output
+    }
+}
+
+struct ClearCommand;
+
+impl Command for ClearCommand {
+    fn name(&self) -> &str {
+        "clear"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["cls"]
+    }
+
+    fn description(&self) -> &str {
+        "clear terminal"
+    }
+
+    fn run(&self, _args: &Args, _state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        CommandOutput {
+            clear: true,
+            ..CommandOutput::default()
+        }
+    }
+}
+
+struct GotoCommand;
+
+impl Command for GotoCommand {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn usage(&self) -> &str {
+        "goto X Y | NAME"
+    }
+
+    fn description(&self) -> &str {
+        "jump to coordinates or a landmark"
+    }
+
+    fn run(&self, args: &Args, state: &mut ShellState, ctx: &Context) -> CommandOutput {
+        // Anything that does not start like a number is a landmark name
+        if args.get(0).is_some_and(|first| !first.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')) {
+            let name = args.rest(0);
+            let Some(landmark) = ctx.world.find_landmark(&name, state.x, state.y) else {
+                return CommandOutput::error(format!("no landmark named '{}' within {} tiles", name, LANDMARK_SEARCH_RANGE));
+            };
+            state.x = landmark.x;
+            state.y = landmark.y;
+            return CommandOutput::line(format!("teleported to {}", landmark));
+        }
+
+        // Any i64 tile works: rendering only ever generates what is on screen
+        let (Some(x), Some(y)) = (args.parse_arg::<i64>(0), args.parse_arg::<i64>(1)) else {
+            return CommandOutput::line("usage: goto <x> <y>");
+        };
+        let (Ok(x), Ok(y)) = (x, y) else {
+            return CommandOutput::error(format!("coordinates must be whole numbers from {} to {}", i64::MIN, i64::MAX));
+        };
+
+        state.x = x;
+        state.y = y;
+        CommandOutput::line(format!("teleported to ({}, {})", x, y))
+    }
+}
+
+struct CatCommand;
+
+impl Command for CatCommand {
+    fn name(&self) -> &str {
+        "cat"
+    }
+
+    fn description(&self) -> &str {
+        "find the ascii cat"
+    }
+
+    fn run(&self, _args: &Args, state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        state.x = state.cat_x;
+        state.y = state.cat_y;
+        CommandOutput::line("found ascii cat! 🐱")
+    }
+}
+
+struct RandomCommand;
+
+impl Command for RandomCommand {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["rnd"]
+    }
+
+    fn description(&self) -> &str {
+        "random location"
+    }
+
+    fn run(&self, _args: &Args, state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        let span = 2.0 * RANDOM_RANGE as f64 + 1.0;
+        state.x = (state.random() * span) as i64 - RANDOM_RANGE;
+        state.y = (state.random() * span) as i64 - RANDOM_RANGE;
+        CommandOutput::line(format!("warped to ({}, {})", state.x, state.y))
+    }
+}
+
+struct PosCommand;
+
+impl Command for PosCommand {
+    fn name(&self) -> &str {
+        "pos"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["position", "where"]
+    }
+
+    fn description(&self) -> &str {
+        "show current position"
+    }
+
+    fn run(&self, _args: &Args, state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        CommandOutput::line(format!("x={} y={}", state.x, state.y))
+    }
+}
+
+struct LandmarksCommand;
+
+impl Command for LandmarksCommand {
+    fn name(&self) -> &str {
+        "landmarks"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["places"]
+    }
+
+    fn description(&self) -> &str {
+        "list named places nearby"
+    }
+
+    fn run(&self, _args: &Args, state: &mut ShellState, ctx: &Context) -> CommandOutput {
+        let (x, y) = (state.x, state.y);
+        let mut landmarks = ctx.world.landmarks_in(Rect::around(x, y, LANDMARKS_RADIUS));
+        let distance = |lx: i64, ly: i64| (lx.abs_diff(x) as f64).hypot(ly.abs_diff(y) as f64);
+        landmarks.sort_by(|a, b| distance(a.x, a.y).total_cmp(&distance(b.x, b.y)));
+        if landmarks.is_empty() {
+            return CommandOutput::line(format!("no landmarks within {} tiles", LANDMARKS_RADIUS));
+        }
+
+        let mut output = CommandOutput::line(format!("landmarks near ({}, {}):", x, y));
+        for landmark in landmarks.iter().take(LANDMARKS_SHOWN) {
+            let (dx, dy) = (landmark.x.wrapping_sub(x), landmark.y.wrapping_sub(y));
+            output.push(format!(
+                "  {} - {} at ({}, {}), {:.0} tiles {}",
+                landmark.name,
+                landmark.kind.name(),
+                landmark.x,
+                landmark.y,
+                distance(landmark.x, landmark.y),
+                compass(dx, dy)
+            ));
+        }
+        output
+    }
+}
+
+/// Compass direction of an offset (north is up, towards negative y)
+fn compass(dx: i64, dy: i64) -> &'static str {
+    if dx == 0 && dy == 0 {
+        return "here";
+    }
+    const DIRECTIONS: [&str; 8] = ["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+    let angle = (-(dy as f64)).atan2(dx as f64).rem_euclid(std::f64::consts::TAU);
+    DIRECTIONS[(angle / std::f64::consts::FRAC_PI_4).round() as usize % 8]
+}
+
+struct ThemeCommand;
+
+impl Command for ThemeCommand {
+    fn name(&self) -> &str {
+        "theme"
+    }
+
+    fn usage(&self) -> &str {
+        "theme NAME"
+    }
+
+    fn description(&self) -> &str {
+        "switch colours (no name: list)"
+    }
+
+    fn run(&self, args: &Args, state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+        let names: Vec<&str> = state.themes.iter().map(|theme| theme.name()).collect();
+        let Some(name) = args.get(0) else {
+            return CommandOutput::line(format!("theme: {} (available: {})", state.theme.name(), names.join(", ")));
+        };
+
+        match state.themes.iter().find(|theme| theme.name().eq_ignore_ascii_case(name)) {
+            Some(theme) => {
+                state.theme = theme.clone();
+                CommandOutput::line(format!("theme set to {}", theme.name()))
+            }
+            None => CommandOutput::error(format!("unknown theme '{}' (available: {})", name, names.join(", "))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, state: &mut ShellState) -> CommandOutput {
+        Shell::new().execute(input, state, &World::new())
+    }
+
+    #[test]
+    fn test_args_parsing() {
+        let args = Args::parse("  GOTO 10   -20 ").unwrap();
+        assert_eq!(args.name(), "goto");
+        assert_eq!(args.len(), 2);
+        assert_eq!(args.parse_arg::<i32>(1), Some(Ok(-20)));
+        assert_eq!(args.get(2), None);
+
+        let quoted = Args::parse("say \"hello  world\" again").unwrap();
+        assert_eq!(quoted.get(0), Some("hello  world"));
+        assert_eq!(quoted.rest(0), "hello  world again");
+
+        assert!(Args::parse("   ").is_none());
+    }
+
+    #[test]
+    fn test_goto() {
+        let mut state = ShellState::new(1);
+
+        let output = run("goto 100 -50", &mut state);
+        assert_eq!(output.lines, vec![format!("{}goto 100 -50", PROMPT), "teleported to (100, -50)".to_string()]);
+        assert_eq!((state.x, state.y), (100, -50));
+
+        assert_eq!(run("goto 1", &mut state).lines[1], "usage: goto <x> <y>");
+        assert!(run("goto 1 b", &mut state).lines[1].starts_with("error: coordinates must be whole numbers"));
+        assert!(run("goto 1.5 0", &mut state).lines[1].starts_with("error:"));
+        assert!(run("goto 9223372036854775808 0", &mut state).lines[1].starts_with("error:"));
+        assert_eq!((state.x, state.y), (100, -50));
+
+        run("goto -9223372036854775808 9223372036854775807", &mut state);
+        assert_eq!((state.x, state.y), (i64::MIN, i64::MAX));
+        assert_eq!(state.history.len(), 6);
+    }
+
+    #[test]
+    fn test_cat_pos_random_clear() {
+        let mut state = ShellState::new(7);
+        state.cat_x = 12;
+        state.cat_y = 34;
+
+        run("cat", &mut state);
+        assert_eq!(run("where", &mut state).lines[1], "x=12 y=34");
+
+        run("rnd", &mut state);
+        assert!(state.x.abs() <= RANDOM_RANGE && state.y.abs() <= RANDOM_RANGE);
+        let mut replay = ShellState::new(7);
+        run("random", &mut replay);
+        assert_eq!((state.x, state.y), (replay.x, replay.y));
+
+        let cleared = run("cls", &mut state);
+        assert!(cleared.clear);
+        assert!(cleared.lines.is_empty());
+    }
+
+    #[test]
+    fn test_landmarks_and_goto_by_name() {
+        let world = World::new();
+        let shell = Shell::new();
+        let mut state = ShellState::new(1);
+
+        let listed = shell.execute("landmarks", &mut state, &world);
+        assert_eq!(listed.lines[1], "landmarks near (0, 0):");
+        assert_eq!(listed.lines.len(), 2 + LANDMARKS_SHOWN);
+        let nearest = world.nearest_landmark(0, 0).unwrap();
+        assert!(listed.lines[2].starts_with(&format!("  {} - {} at ({}, {}),", nearest.name, nearest.kind.name(), nearest.x, nearest.y)));
+
+        // Names are matched ignoring case, from wherever the user is
+        let far = world.landmarks_in(Rect::around(1500, -900, 200)).pop().unwrap();
+        state.x = 1000;
+        let output = shell.execute(&format!("goto {}", far.name.to_uppercase()), &mut state, &world);
+        assert_eq!(output.lines[1], format!("teleported to {}", far));
+        assert_eq!((state.x, state.y), (far.x, far.y));
+
+        let missing = shell.execute("goto Nowhere Special", &mut state, &world);
+        assert_eq!(missing.lines[1], format!("error: no landmark named 'Nowhere Special' within {} tiles", LANDMARK_SEARCH_RANGE));
+        assert_eq!((state.x, state.y), (far.x, far.y));
+
+        assert_eq!(compass(3, -3), "NE");
+        assert_eq!(compass(0, 5), "S");
+    }
+
+    #[test]
+    fn test_theme() {
+        let mut state = ShellState::new(1);
+        assert_eq!(state.theme.name(), "classic");
+        assert!(run("theme", &mut state).lines[1].contains("amber, solarized, high-contrast, monochrome"));
+
+        assert_eq!(run("theme Solarized", &mut state).lines[1], "theme set to solarized");
+        assert_eq!(state.theme, Theme::solarized());
+        assert!(run("theme neon", &mut state).lines[1].starts_with("error: unknown theme 'neon'"));
+        assert_eq!(state.theme.name(), "solarized");
+
+        // Themes loaded by the frontend can be picked too
+        state.themes.push(Theme::amber().with_name("dusk"));
+        run("theme dusk", &mut state);
+        assert_eq!(state.theme.name(), "dusk");
+    }
+
+    #[test]
+    fn test_custom_command_and_help() {
+        struct Origin;
+
+        impl Command for Origin {
+            fn name(&self) -> &str {
+                "origin"
+            }
+
+            fn description(&self) -> &str {
+                "back to (0, 0)"
+            }
+
+            fn run(&self, _args: &Args, state: &mut ShellState, _ctx: &Context) -> CommandOutput {
+                state.x = 0;
+                state.y = 0;
+                CommandOutput::new()
+            }
+        }
+
+        let mut shell = Shell::new();
+        shell.register(Box::new(Origin));
+        let world = World::new();
+        let mut state = ShellState::new(1);
+        state.x = 5;
+
+        shell.execute("origin", &mut state, &world);
+        assert_eq!(state.x, 0);
+
+        let help = shell.execute("help", &mut state, &world);
+        assert!(help.lines.iter().any(|l| l.contains("origin") && l.contains("back to (0, 0)")));
+        assert!(help.lines.iter().any(|l| l.contains("goto X Y")));
+
+        let unknown = shell.execute("fly", &mut state, &world);
+        assert_eq!(unknown.lines[1], "command not found: fly");
+    }
+}
@@ -0,0 +1,495 @@
+//! Entities for EndlessUtopia
+//! Creatures spawn deterministically per chunk and move as a pure function of the tick
+
+use crate::region::Rect;
+use crate::world::{Biome, Tile, World, HASH_MUL};
+
+/// The Ascicat roams within +-CAT_ROAM_RADIUS tiles of its home
+pub const CAT_ROAM_RADIUS: i32 = 24;
+
+/// Side of the Ascicat's spawn cell in chunks (2^32 tiles): four cells cover its whole home range
+pub const ASCICAT_CELL_CHUNKS: u32 = 1 << 27;
+
+/// Ticks per leg of the Ascicat's walk: it walks one tile per tick towards the
+/// next waypoint (at most 2 * CAT_ROAM_RADIUS ticks), then sits until the leg ends
+pub const CAT_LEG_TICKS: u64 = 64;
+
+/// Radii and flee distances are clamped to 0..=MAX_ROAM_RADIUS tiles
+pub const MAX_ROAM_RADIUS: i32 = 1 << 16;
+
+/// How an entity moves. Every behaviour is a pure function of the tick (and the viewer),
+/// so any moment can be computed without simulating the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behaviour {
+    /// Walk between seeded waypoints within `radius` of home, one tile per tick.
+    /// Each leg lasts `leg_ticks`, which should be at least `2 * radius`.
+    Wander { radius: i32, leg_ticks: u64 },
+    /// Follow a closed path through `waypoints` seeded points within `radius` of home
+    Path { waypoints: u32, radius: i32 },
+    /// Wander, but stay at least `distance` tiles away from the viewer
+    Flee { radius: i32, leg_ticks: u64, distance: i32 },
+}
+
+impl Behaviour {
+    /// How far from home the entity can get
+    pub fn reach(&self) -> i32 {
+        match *self {
+            Behaviour::Wander { radius, .. } | Behaviour::Path { radius, .. } => roam(radius),
+            Behaviour::Flee { radius, distance, .. } => roam(radius) + roam(distance),
+        }
+    }
+
+    /// Position at `tick` of the entity with this `id` and `home`
+    pub fn position(&self, id: u64, home: (i64, i64), tick: u64, viewer: Option<(i64, i64)>) -> (i64, i64) {
+        match *self {
+            Behaviour::Wander { radius, leg_ticks } => wander(id, home, radius, leg_ticks, tick),
+            Behaviour::Path { waypoints, radius } => follow_path(id, home, waypoints, radius, tick),
+            Behaviour::Flee { radius, leg_ticks, distance } => {
+                let position = wander(id, home, radius, leg_ticks, tick);
+                match viewer {
+                    Some(viewer) => flee(id, position, viewer, distance),
+                    None => position,
+                }
+            }
+        }
+    }
+}
+
+/// `radius` or `distance` of a behaviour, clamped to 0..=MAX_ROAM_RADIUS
+fn roam(radius: i32) -> i32 {
+    radius.clamp(0, MAX_ROAM_RADIUS)
+}
+
+/// Seeded point within `radius` of home; point 0 is home itself
+fn waypoint(id: u64, (home_x, home_y): (i64, i64), radius: i32, index: u64) -> (i64, i64) {
+    if index == 0 {
+        return (home_x, home_y);
+    }
+
+    let mut h = id;
+    h = h.wrapping_mul(HASH_MUL);
+    h ^= index;
+    h = h.wrapping_mul(HASH_MUL);
+    let radius = roam(radius);
+    let span = (2 * radius + 1) as u64;
+    let dx = ((h >> 40) % span) as i64 - radius as i64;
+    let dy = (((h >> 16) & 0xFF_FFFF) % span) as i64 - radius as i64;
+    (home_x.saturating_add(dx), home_y.saturating_add(dy))
+}
+
+/// Move from `from` towards `to` by `steps` tiles: diagonal first, then straight
+fn advance((from_x, from_y): (i64, i64), (to_x, to_y): (i64, i64), steps: u64) -> (i64, i64) {
+    let axis = |from: i64, to: i64| {
+        let distance = from.abs_diff(to).min(steps) as i128;
+        (from as i128 + (to as i128 - from as i128).signum() * distance) as i64
+    };
+    (axis(from_x, to_x), axis(from_y, to_y))
+}
+
+/// Tiles needed to walk from `a` to `b`
+fn walk_length(a: (i64, i64), b: (i64, i64)) -> u64 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+fn wander(id: u64, home: (i64, i64), radius: i32, leg_ticks: u64, tick: u64) -> (i64, i64) {
+    let leg_ticks = leg_ticks.max(1);
+    let leg = tick / leg_ticks;
+    let from = waypoint(id, home, radius, leg);
+    let to = waypoint(id, home, radius, leg + 1);
+    advance(from, to, tick % leg_ticks)
+}
+
+fn follow_path(id: u64, home: (i64, i64), waypoints: u32, radius: i32, tick: u64) -> (i64, i64) {
+    let points = waypoints.max(1) as u64;
+    let point = |i: u64| waypoint(id, home, radius, i % points);
+    let total: u64 = (0..points).map(|i| walk_length(point(i), point(i + 1))).sum();
+    if total == 0 {
+        return home;
+    }
+
+    // Entities sharing a path shape still start at different places along it
+    let mut t = tick.wrapping_add(id) % total;
+    for i in 0..points {
+        let length = walk_length(point(i), point(i + 1));
+        if t < length {
+            return advance(point(i), point(i + 1), t);
+        }
+        t -= length;
+    }
+    home
+}
+
+/// Push `position` straight away from the viewer until it is `distance` tiles away
+fn flee(id: u64, (x, y): (i64, i64), (viewer_x, viewer_y): (i64, i64), distance: i32) -> (i64, i64) {
+    let distance = roam(distance);
+    let gap = walk_length((x, y), (viewer_x, viewer_y));
+    if gap >= distance as u64 {
+        return (x, y);
+    }
+
+    let push = distance as i64 - gap as i64;
+    let mut dx = (x as i128 - viewer_x as i128).signum() as i64;
+    let mut dy = (y as i128 - viewer_y as i128).signum() as i64;
+    if dx == 0 && dy == 0 {
+        // Right under the viewer: pick a seeded direction
+        dx = if id & 1 == 0 { 1 } else { -1 };
+        dy = if id & 2 == 0 { 1 } else { -1 };
+    }
+    (x.saturating_add(dx * push), y.saturating_add(dy * push))
+}
+
+/// Home tile inside spawn `cell` for a spawn that happens with probability
+/// `per_mille` / 1000; `hash` decides both whether and where
+pub fn chance_in(cell: Rect, hash: u64, per_mille: u32) -> Option<(i64, i64)> {
+    if hash % 1000 >= per_mille as u64 {
+        return None;
+    }
+    let dx = (hash >> 20) % cell.width.max(1) as u64;
+    let dy = (hash >> 40) % cell.height.max(1) as u64;
+    Some((cell.x.saturating_add(dx as i64), cell.y.saturating_add(dy as i64)))
+}
+
+/// A kind of creature that can be registered with a `World` (see `WorldBuilder::entity`)
+pub trait Entity: Send + Sync {
+    /// Short, unique name, e.g. "bird" - registering a second kind
+    /// with the same name replaces the first one
+    fn name(&self) -> &str;
+
+    /// Biome reported on the tile the entity stands on
+    fn biome(&self) -> Biome;
+
+    /// How it moves
+    fn behaviour(&self) -> Behaviour;
+
+    /// Side of this kind's square spawn cells, in chunks. Rare kinds use large cells
+    /// so searches over big areas visit few of them.
+    fn spawn_cell_chunks(&self) -> u32 {
+        1
+    }
+
+    /// Home of this kind's entity in spawn `cell` (tile rectangle), if one spawns there
+    /// (at most one per cell). `hash` is seeded and unique per world, kind and cell.
+    fn spawn(&self, world: &World, cell: Rect, hash: u64) -> Option<(i64, i64)>;
+
+    /// Homes of this kind's entities inside `rect`, for kinds that know them without
+    /// visiting spawn cells (e.g. one entity per world). `None` walks the cells with `spawn`.
+    fn homes_in(&self, _world: &World, _rect: Rect) -> Option<Vec<(i64, i64)>> {
+        None
+    }
+
+    /// Glyph at `tick`; `hash` is the seeded hash of the tile it stands on
+    fn glyph(&self, tick: u64, hash: u64) -> char;
+}
+
+/// Entry of a spawn table: an entity and its home, independent of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+    /// Stable identity across ticks
+    pub id: u64,
+    /// Index of its kind in the world's `EntityRegistry`
+    pub kind: usize,
+    pub home: (i64, i64),
+}
+
+/// One live entity at a given tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Creature {
+    /// Stable identity across ticks
+    pub id: u64,
+    /// Index of its kind in the world's `EntityRegistry`
+    pub kind: usize,
+    pub home: (i64, i64),
+    pub x: i64,
+    pub y: i64,
+    /// What is drawn where it stands
+    pub tile: Tile,
+}
+
+/// The one true Ascicat: a single cat per world, living near `World::ascicat_position`
+pub struct Ascicat;
+
+impl Entity for Ascicat {
+    fn name(&self) -> &str {
+        "ascicat"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::CatPresent
+    }
+
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Wander {
+            radius: CAT_ROAM_RADIUS,
+            leg_ticks: CAT_LEG_TICKS,
+        }
+    }
+
+    fn spawn_cell_chunks(&self) -> u32 {
+        ASCICAT_CELL_CHUNKS
+    }
+
+    fn spawn(&self, world: &World, cell: Rect, _hash: u64) -> Option<(i64, i64)> {
+        let (home_x, home_y) = world.ascicat_position();
+        cell.contains(home_x, home_y).then_some((home_x, home_y))
+    }
+
+    fn homes_in(&self, world: &World, rect: Rect) -> Option<Vec<(i64, i64)>> {
+        let (home_x, home_y) = world.ascicat_position();
+        Some(rect.contains(home_x, home_y).then_some((home_x, home_y)).into_iter().collect())
+    }
+
+    fn glyph(&self, _tick: u64, hash: u64) -> char {
+        // Use basic ASCII for better WASM compatibility
+        let basic_poses = ['@', 'C', 'c', 'o', 'O'];
+        basic_poses[(hash % basic_poses.len() as u64) as usize]
+    }
+}
+
+/// Rare stray cats: one in every other 16x16-chunk cell, roughly one in 500 chunks
+pub struct StrayCat;
+
+impl Entity for StrayCat {
+    fn name(&self) -> &str {
+        "stray-cat"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::CatPresent
+    }
+
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Wander {
+            radius: 12,
+            leg_ticks: 32,
+        }
+    }
+
+    fn spawn_cell_chunks(&self) -> u32 {
+        16
+    }
+
+    fn spawn(&self, _world: &World, cell: Rect, hash: u64) -> Option<(i64, i64)> {
+        chance_in(cell, hash, 500)
+    }
+
+    fn glyph(&self, tick: u64, hash: u64) -> char {
+        Ascicat.glyph(tick, hash)
+    }
+}
+
+/// Birds circling over calm areas; they scatter when the viewer comes close
+pub struct Bird;
+
+impl Entity for Bird {
+    fn name(&self) -> &str {
+        "bird"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::Creature("bird")
+    }
+
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Flee {
+            radius: 16,
+            leg_ticks: 32,
+            distance: 6,
+        }
+    }
+
+    fn spawn(&self, world: &World, cell: Rect, hash: u64) -> Option<(i64, i64)> {
+        let (x, y) = chance_in(cell, hash, 250)?;
+        (world.base_tile(x, y).biome == Biome::Calm).then_some((x, y))
+    }
+
+    fn glyph(&self, tick: u64, _hash: u64) -> char {
+        // Wings up, wings down
+        ['v', '^'][(tick % 2) as usize]
+    }
+}
+
+/// Glitch mites crawling along short loops inside glitch zones
+pub struct GlitchMite;
+
+impl Entity for GlitchMite {
+    fn name(&self) -> &str {
+        "glitch-mite"
+    }
+
+    fn biome(&self) -> Biome {
+        Biome::Creature("glitch-mite")
+    }
+
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Path {
+            waypoints: 4,
+            radius: 6,
+        }
+    }
+
+    fn spawn(&self, world: &World, cell: Rect, hash: u64) -> Option<(i64, i64)> {
+        let (x, y) = chance_in(cell, hash, 150)?;
+        (world.base_tile(x, y).biome == Biome::Glitch).then_some((x, y))
+    }
+
+    fn glyph(&self, tick: u64, _hash: u64) -> char {
+        ['*', '+', 'x', '+'][(tick % 4) as usize]
+    }
+}
+
+/// The entity kinds of a world, in registration order
+pub struct EntityRegistry {
+    kinds: Vec<Box<dyn Entity>>,
+}
+
+impl EntityRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        EntityRegistry { kinds: Vec::new() }
+    }
+
+    /// Just the Ascicat (the original world)
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(Ascicat));
+        registry
+    }
+
+    /// The Ascicat plus stray cats, birds and glitch mites
+    pub fn with_creatures() -> Self {
+        let mut registry = Self::with_defaults();
+        registry.register(Box::new(StrayCat));
+        registry.register(Box::new(Bird));
+        registry.register(Box::new(GlitchMite));
+        registry
+    }
+
+    /// Register a kind; a kind with the same name is replaced in place
+    pub fn register(&mut self, kind: Box<dyn Entity>) {
+        match self.kinds.iter_mut().find(|k| k.name() == kind.name()) {
+            Some(existing) => *existing = kind,
+            None => self.kinds.push(kind),
+        }
+    }
+
+    /// Remove every registered kind
+    pub fn clear(&mut self) {
+        self.kinds.clear();
+    }
+
+    /// Kind by index (see `Creature::kind`)
+    pub fn get(&self, index: usize) -> Option<&dyn Entity> {
+        self.kinds.get(index).map(|k| k.as_ref())
+    }
+
+    /// Registered kinds, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Entity> {
+        self.kinds.iter().map(|k| k.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+
+    /// Furthest any registered kind can get from its home
+    pub fn reach(&self) -> i32 {
+        self.iter().map(|k| k.behaviour().reach()).max().unwrap_or(0)
+    }
+}
+
+impl Default for EntityRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wander_stays_home_and_steps() {
+        let behaviour = Behaviour::Wander {
+            radius: 5,
+            leg_ticks: 12,
+        };
+        assert_eq!(behaviour.position(42, (100, -100), 0, None), (100, -100));
+
+        let mut previous = (100, -100);
+        for tick in 1..500 {
+            let (x, y) = behaviour.position(42, (100, -100), tick, None);
+            assert!((x - 100).abs() <= 5 && (y + 100).abs() <= 5);
+            assert!((x - previous.0).abs() <= 1 && (y - previous.1).abs() <= 1);
+            previous = (x, y);
+        }
+    }
+
+    #[test]
+    fn test_path_loops() {
+        let behaviour = Behaviour::Path { waypoints: 4, radius: 6 };
+        let positions: Vec<_> = (0..200).map(|t| behaviour.position(7, (0, 0), t, None)).collect();
+
+        for pair in positions.windows(2) {
+            assert!((pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1);
+        }
+        // Back where it was after one lap
+        let lap = (1..200).find(|&t| positions[t as usize..].starts_with(&positions[..5])).unwrap();
+        assert!(lap > 1);
+    }
+
+    #[test]
+    fn test_flee_keeps_distance() {
+        let behaviour = Behaviour::Flee {
+            radius: 8,
+            leg_ticks: 16,
+            distance: 6,
+        };
+        for tick in 0..100 {
+            let calm = behaviour.position(3, (0, 0), tick, None);
+            let (x, y) = behaviour.position(3, (0, 0), tick, Some(calm));
+            assert!((x - calm.0).abs().max((y - calm.1).abs()) >= 6);
+        }
+        assert_eq!(behaviour.reach(), 14);
+    }
+
+    #[test]
+    fn test_radius_is_clamped() {
+        for radius in [i32::MIN, -1, 0] {
+            let behaviour = Behaviour::Wander { radius, leg_ticks: 4 };
+            assert_eq!(behaviour.reach(), 0);
+            assert_eq!(behaviour.position(9, (5, 5), 37, None), (5, 5));
+        }
+
+        let behaviour = Behaviour::Flee {
+            radius: i32::MAX,
+            leg_ticks: 1,
+            distance: i32::MAX,
+        };
+        assert_eq!(behaviour.reach(), 2 * MAX_ROAM_RADIUS);
+        for tick in 0..50 {
+            let (x, y) = Behaviour::Path { waypoints: 3, radius: i32::MAX }.position(tick, (0, 0), tick, None);
+            assert!(x.abs() <= MAX_ROAM_RADIUS as i64 && y.abs() <= MAX_ROAM_RADIUS as i64);
+            let (x, y) = behaviour.position(tick, (i64::MAX, i64::MIN), tick, Some((0, 0)));
+            assert!(x > 0 && y < 0);
+        }
+    }
+
+    #[test]
+    fn test_registry_replaces_by_name() {
+        let mut registry = EntityRegistry::with_creatures();
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.reach(), CAT_ROAM_RADIUS);
+
+        registry.register(Box::new(Bird));
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.get(0).unwrap().name(), "ascicat");
+
+        registry.clear();
+        assert!(registry.is_empty());
+        assert_eq!(registry.reach(), 0);
+    }
+}
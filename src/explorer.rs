@@ -0,0 +1,244 @@
+//! Exploration state for EndlessUtopia
+//! An `Explorer` walks a shared, immutable `World` and remembers the cat's trail
+
+use std::sync::Arc;
+
+use crate::region::{Rect, Region};
+use crate::trail::{Trail, TrailStep, TRAIL_TICKS};
+use crate::world::{Biome, Tile, World, NORMAL_INTENSITY};
+
+/// Mutable exploration state (current tick, viewer, cat trail) on top of a shared `World`
+pub struct Explorer {
+    world: Arc<World>,
+    tick: u64,
+    viewer: Option<(i64, i64)>,
+    trail: Trail,
+}
+
+impl Explorer {
+    /// Start exploring a world at tick 0; pass an `Arc<World>` to share one generator
+    pub fn new(world: impl Into<Arc<World>>) -> Self {
+        let mut explorer = Explorer {
+            world: world.into(),
+            tick: 0,
+            viewer: None,
+            trail: Trail::new(),
+        };
+        explorer.record_trail(0);
+        explorer
+    }
+
+    /// The world being explored
+    pub fn world(&self) -> &Arc<World> {
+        &self.world
+    }
+
+    /// Current tick; decides where the cat is
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Advance (or rewind) time, e.g. to `epoch_millis / CAT_TICK_MILLIS`.
+    /// The trail is filled in for every tick in between (at most `TRAIL_TICKS`).
+    pub fn set_tick(&mut self, tick: u64) {
+        if tick < self.tick {
+            self.trail.rewind(tick);
+        }
+        let from = match self.trail.last() {
+            Some(last) if last.tick <= tick => last.tick + 1,
+            _ => 0,
+        };
+        self.tick = tick;
+        self.record_trail(from.max(tick.saturating_sub(TRAIL_TICKS)));
+    }
+
+    /// Where the viewer is looking from (usually the centre of the view);
+    /// shy entities keep their distance from it
+    pub fn viewer(&self) -> Option<(i64, i64)> {
+        self.viewer
+    }
+
+    pub fn set_viewer(&mut self, viewer: Option<(i64, i64)>) {
+        self.viewer = viewer;
+    }
+
+    /// Record the cat's steps from tick `from` up to the current tick
+    fn record_trail(&mut self, from: u64) {
+        for tick in from..=self.tick {
+            let (x, y) = self.world.cat_position_at(tick);
+            self.trail.record(x, y, tick);
+        }
+    }
+
+    /// Where the cat is right now
+    pub fn cat_position(&self) -> (i64, i64) {
+        self.world.cat_position_at(self.tick)
+    }
+
+    /// The cat's recent path, oldest step first
+    pub fn trail(&self) -> &Trail {
+        &self.trail
+    }
+
+    /// Replace the recorded trail, e.g. with one restored from a saved `Session`
+    pub fn set_trail(&mut self, trail: Trail) {
+        self.trail = trail;
+    }
+
+    /// Trail steps inside `rect`, oldest first
+    pub fn trail_in(&self, rect: Rect) -> impl Iterator<Item = &TrailStep> {
+        self.trail.in_rect(rect)
+    }
+
+    /// Trace tile for a step, faded by its age
+    fn trace_tile(&self, step: &TrailStep) -> Tile {
+        let age = step.age(self.tick);
+        let tile = Tile::new(self.world.get_trace_char(age), Biome::CatTrace);
+        // Fresh steps at normal intensity, the oldest at a third of it
+        let fade = age.min(TRAIL_TICKS) * (NORMAL_INTENSITY as u64 * 2 / 3) / TRAIL_TICKS.max(1);
+        Tile {
+            intensity: NORMAL_INTENSITY - fade as u8,
+            ..tile
+        }
+    }
+
+    /// Get a tile, with entities or the cat's trace
+    pub fn get_tile(&self, x: i64, y: i64) -> Tile {
+        let tile = self.world.get_tile_viewed(x, y, self.tick, self.viewer);
+        if matches!(tile.biome, Biome::CatPresent | Biome::Creature(_)) {
+            return tile;
+        }
+
+        match self.trail.latest_at(x, y) {
+            Some(step) => self.trace_tile(step),
+            None => tile,
+        }
+    }
+
+    /// Fill a region buffer, including traces
+    pub fn fill_region(&self, region: &mut Region) {
+        self.world.fill_region_viewed(region, self.tick, self.viewer);
+
+        // Oldest first, so the freshest visit to a tile wins; entities stay on top
+        for step in self.trail.in_rect(region.rect()) {
+            if let Some(local) = region.local(step.x, step.y) {
+                if !matches!(region[local].biome, Biome::CatPresent | Biome::Creature(_)) {
+                    region[local] = self.trace_tile(step);
+                }
+            }
+        }
+    }
+
+    /// Get a rectangular region, including traces
+    pub fn get_region(&self, x_start: i64, y_start: i64, width: usize, height: usize) -> Vec<Vec<Tile>> {
+        let mut region = Region::new(x_start, y_start, width, height);
+        self.fill_region(&mut region);
+        region.to_rows()
+    }
+
+    /// Render a region to ASCII string, including traces
+    pub fn render_region(&self, x_start: i64, y_start: i64, width: usize, height: usize) -> String {
+        let mut region = Region::new(x_start, y_start, width, height);
+        self.fill_region(&mut region);
+        region.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First tick after `after` at which the cat stands somewhere new
+    fn next_move(world: &World, after: u64) -> u64 {
+        let from = world.cat_position_at(after);
+        (after + 1..).find(|&t| world.cat_position_at(t) != from).unwrap()
+    }
+
+    #[test]
+    fn test_cat_trace_persistence() {
+        let mut explorer = Explorer::new(World::new());
+        let (x, y) = explorer.world().ascicat_position();
+        assert_eq!(explorer.get_tile(x, y).biome, Biome::CatPresent);
+
+        // Once the cat walks on, its old tile shows a fresh trace
+        let moved = next_move(explorer.world(), 0);
+        explorer.set_tick(moved);
+        let trace = explorer.get_tile(x, y);
+        assert_eq!(trace.biome, Biome::CatTrace);
+        assert_eq!(trace.character, '•');
+
+        // Traces fade as they age, then expire
+        assert_eq!(explorer.world().get_trace_char(TRAIL_TICKS / 2), '·');
+        assert_eq!(explorer.world().get_trace_char(TRAIL_TICKS), '.');
+        explorer.set_tick(moved + TRAIL_TICKS / 2);
+        assert!(explorer.get_tile(x, y).intensity < trace.intensity);
+        explorer.set_tick(moved + TRAIL_TICKS * 2);
+        assert!(explorer.trail().iter().all(|step| step.age(explorer.tick()) <= TRAIL_TICKS));
+
+        // The shared world itself is unchanged
+        assert_eq!(explorer.world().get_tile(x, y).biome, Biome::CatPresent);
+    }
+
+    #[test]
+    fn test_region_shows_trail() {
+        let world = Arc::new(World::new());
+        let mut explorer = Explorer::new(world.clone());
+        explorer.set_tick(500);
+
+        let (cat_x, cat_y) = explorer.cat_position();
+        let rect = Rect::around(cat_x, cat_y, 60);
+        let mut region = Region::new(rect.x, rect.y, rect.width, rect.height);
+        explorer.fill_region(&mut region);
+
+        let (local_x, local_y) = region.local(cat_x, cat_y).unwrap();
+        assert_eq!(region[(local_x, local_y)].biome, Biome::CatPresent);
+
+        let steps: Vec<_> = explorer.trail_in(rect).copied().collect();
+        assert!(steps.len() > 1);
+        assert_eq!(steps.last().unwrap().tick, 500);
+        for step in &steps[..steps.len() - 1] {
+            if (step.x, step.y) == (cat_x, cat_y) {
+                continue;
+            }
+            let local = region.local(step.x, step.y).unwrap();
+            assert_eq!(region[local].biome, Biome::CatTrace);
+        }
+    }
+
+    #[test]
+    fn test_cat_follows_tick() {
+        let mut explorer = Explorer::new(World::new());
+        let tick = next_move(explorer.world(), 0);
+
+        explorer.set_tick(tick);
+        let (x, y) = explorer.cat_position();
+        assert_eq!(explorer.tick(), tick);
+        assert_eq!(explorer.get_tile(x, y).biome, Biome::CatPresent);
+        assert_eq!(explorer.trail().last(), Some(&TrailStep { x, y, tick }));
+
+        // Rewinding forgets the future
+        explorer.set_tick(0);
+        assert_eq!(explorer.trail().len(), 1);
+    }
+
+    #[test]
+    fn test_creatures_flee_viewer() {
+        let world = Arc::new(World::builder().with_creatures().build());
+        let mut explorer = Explorer::new(world.clone());
+        let area = Rect::new(-400, -400, 800, 800);
+
+        let birds: Vec<_> = world
+            .creatures_in(area, 0, None)
+            .into_iter()
+            .filter(|c| c.tile.biome == Biome::Creature("bird"))
+            .collect();
+        let bird = birds.first().expect("some birds near the origin");
+
+        // Stand right on the bird: it keeps its distance
+        explorer.set_viewer(Some((bird.x, bird.y)));
+        assert_ne!(explorer.get_tile(bird.x, bird.y).biome, Biome::Creature("bird"));
+        let scattered = world.creatures_in(area, 0, explorer.viewer());
+        let moved = scattered.iter().find(|c| c.id == bird.id).unwrap();
+        assert!((moved.x - bird.x).abs().max((moved.y - bird.y).abs()) >= 6);
+    }
+}
@@ -0,0 +1,353 @@
+//! Region exporters for EndlessUtopia
+//! Writes a filled `Region` as plain text, ANSI-coloured text, standalone HTML or SVG in a `Theme`'s colours
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::region::Region;
+use crate::colour::{Colour, PALETTE};
+use crate::theme::{Role, Theme};
+use crate::world::{Biome, PatternKind, Tile};
+
+/// Character cell size used by SVG output (matches the browser canvas)
+const SVG_CHAR_WIDTH: f64 = 7.2;
+const SVG_CHAR_HEIGHT: f64 = 16.0;
+
+/// Output formats understood by `export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Text,
+    Ansi,
+    Html,
+    Svg,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(ExportFormat::Text),
+            "ansi" => Ok(ExportFormat::Ansi),
+            "html" | "htm" => Ok(ExportFormat::Html),
+            "svg" => Ok(ExportFormat::Svg),
+            other => Err(format!("unknown format: {} (expected text, ansi, html or svg)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ExportFormat::Text => "text",
+            ExportFormat::Ansi => "ansi",
+            ExportFormat::Html => "html",
+            ExportFormat::Svg => "svg",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Write a region in the given format, coloured by `theme` (plain text has no colours)
+pub fn export(region: &Region, format: ExportFormat, theme: &Theme, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        ExportFormat::Text => write_text(region, out),
+        ExportFormat::Ansi => write_ansi(region, theme, out),
+        ExportFormat::Html => write_html(region, theme, out),
+        ExportFormat::Svg => write_svg(region, theme, out),
+    }
+}
+
+/// Plain text, one line per row (same as `World::render_region`)
+pub fn write_text(region: &Region, out: &mut impl Write) -> io::Result<()> {
+    write!(out, "{}", region)
+}
+
+/// Text coloured per tile (foreground, background and intensity) with ANSI escape codes;
+/// the terminal keeps its own background
+pub fn write_ansi(region: &Region, theme: &Theme, out: &mut impl Write) -> io::Result<()> {
+    for row in region.rows() {
+        for (tile, text) in style_runs(row, theme) {
+            write!(out, "\x1b[{}m{}", ansi_style(&tile), text)?;
+        }
+        writeln!(out, "\x1b[0m")?;
+    }
+    Ok(())
+}
+
+/// Standalone HTML page with a `<pre>`; spans carry biome, pattern and colour classes
+pub fn write_html(region: &Region, theme: &Theme, out: &mut impl Write) -> io::Result<()> {
+    let (x, y) = region.origin();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html>")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>EndlessUtopia ({}, {}) {}x{}</title>", x, y, region.width(), region.height())?;
+    writeln!(out, "<style>")?;
+    writeln!(out, "body {{ background: {}; margin: 0; }}", theme.ui(Role::Background))?;
+    writeln!(out, "pre.world {{ color: {}; font: 12px monospace; line-height: 16px; margin: 8px; }}", theme.ui(Role::World))?;
+    write_palette_css(out, "color", Some("background"))?;
+    writeln!(out, "</style>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    write!(out, "<pre class=\"world\">")?;
+    for row in region.rows() {
+        for (tile, text) in style_runs(row, theme) {
+            write!(out, "<span class=\"{}\"{}>{}</span>", tile_classes(&tile), inline_style(&tile, "color"), escape_xml(&text))?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "</pre>")?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")
+}
+
+/// SVG image with one `<text>` element per row
+pub fn write_svg(region: &Region, theme: &Theme, out: &mut impl Write) -> io::Result<()> {
+    let width = region.width() as f64 * SVG_CHAR_WIDTH;
+    let height = region.height() as f64 * SVG_CHAR_HEIGHT;
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    )?;
+    writeln!(out, "<style>")?;
+    writeln!(out, "text {{ font: 12px monospace; white-space: pre; fill: {}; }}", theme.ui(Role::World))?;
+    write_palette_css(out, "fill", None)?;
+    writeln!(out, "</style>")?;
+    writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", theme.ui(Role::Background))?;
+    for (index, row) in region.rows().enumerate() {
+        let baseline = (index + 1) as f64 * SVG_CHAR_HEIGHT - 4.0;
+        write!(out, "<text x=\"0\" y=\"{}\" xml:space=\"preserve\">", baseline)?;
+        for (tile, text) in style_runs(row, theme) {
+            write!(out, "<tspan class=\"{}\"{}>{}</tspan>", tile_classes(&tile), inline_style(&tile, "fill"), escape_xml(&text))?;
+        }
+        writeln!(out, "</text>")?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// Recolour a row with `theme` and split it into runs of consecutive tiles that
+/// differ only in their character; each run is returned with its first tile
+fn style_runs(row: &[Tile], theme: &Theme) -> Vec<(Tile, String)> {
+    let mut runs: Vec<(Tile, String)> = Vec::new();
+    for tile in row.iter().map(|&tile| theme.apply(tile)) {
+        match runs.last_mut() {
+            Some((first, text)) if Tile { character: tile.character, ..*first } == tile => text.push(tile.character),
+            _ => runs.push((tile, tile.character.to_string())),
+        }
+    }
+    runs
+}
+
+/// CSS rules for the palette colour classes and intensity levels
+fn write_palette_css(out: &mut impl Write, fg_property: &str, bg_property: Option<&str>) -> io::Result<()> {
+    for index in 0..PALETTE.len() as u8 {
+        writeln!(out, ".fg-{} {{ {}: {}; }}", index, fg_property, Colour::Palette(index))?;
+        if let Some(bg_property) = bg_property {
+            writeln!(out, ".bg-{} {{ {}: {}; }}", index, bg_property, Colour::Palette(index))?;
+        }
+    }
+    writeln!(out, ".dim {{ opacity: 0.6; }}")?;
+    writeln!(out, ".bright {{ font-weight: bold; }}")
+}
+
+/// Space-separated classes of a tile: biome, pattern kind, palette colours and intensity
+fn tile_classes(tile: &Tile) -> String {
+    let mut classes = biome_class(tile.biome);
+    if tile.pattern != PatternKind::Plain {
+        classes.push(' ');
+        classes.push_str(tile.pattern.name());
+    }
+    if let Colour::Palette(index) = tile.fg {
+        classes.push_str(&format!(" fg-{}", index as usize % PALETTE.len()));
+    }
+    if let Some(Colour::Palette(index)) = tile.bg {
+        classes.push_str(&format!(" bg-{}", index as usize % PALETTE.len()));
+    }
+    match intensity_level(tile.intensity) {
+        Intensity::Dim => classes.push_str(" dim"),
+        Intensity::Bright => classes.push_str(" bright"),
+        Intensity::Normal => {}
+    }
+    classes
+}
+
+/// ` style="..."` for RGB colours, which have no class
+fn inline_style(tile: &Tile, fg_property: &str) -> String {
+    let mut style = String::new();
+    if let Colour::Rgb(..) = tile.fg {
+        style.push_str(&format!("{}: {};", fg_property, tile.fg));
+    }
+    if let (Some(bg @ Colour::Rgb(..)), "color") = (tile.bg, fg_property) {
+        style.push_str(&format!("background: {};", bg));
+    }
+    if style.is_empty() {
+        style
+    } else {
+        format!(" style=\"{}\"", style)
+    }
+}
+
+/// CSS class used for a biome in HTML and SVG output
+pub fn biome_class(biome: Biome) -> String {
+    match biome {
+        Biome::Calm => "calm".to_string(),
+        Biome::Pattern => "pattern".to_string(),
+        Biome::Glitch => "glitch".to_string(),
+        Biome::CatTrace => "cat-trace".to_string(),
+        Biome::CatPresent => "cat".to_string(),
+        Biome::Custom(name) => format!("custom-{}", class_name(name)),
+        Biome::Creature(name) => format!("creature-{}", class_name(name)),
+        Biome::Structure => "structure".to_string(),
+    }
+}
+
+/// Lower-case a name and replace anything but letters and digits with '-'
+fn class_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+/// How renderers without an alpha channel show a tile's intensity
+enum Intensity {
+    Dim,
+    Normal,
+    Bright,
+}
+
+fn intensity_level(intensity: u8) -> Intensity {
+    match intensity {
+        0..=127 => Intensity::Dim,
+        u8::MAX => Intensity::Bright,
+        _ => Intensity::Normal,
+    }
+}
+
+/// SGR parameters for a tile (resetting whatever the previous run set)
+fn ansi_style(tile: &Tile) -> String {
+    let mut style = match intensity_level(tile.intensity) {
+        Intensity::Dim => "0;2;".to_string(),
+        Intensity::Normal => "0;".to_string(),
+        Intensity::Bright => "0;1;".to_string(),
+    };
+    style.push_str(&tile.fg.ansi(false));
+    if let Some(bg) = tile.bg {
+        style.push(';');
+        style.push_str(&bg.ansi(true));
+    }
+    style
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    fn sample() -> Region {
+        let world = World::new();
+        let mut region = Region::new(1000, -500, 30, 10);
+        world.fill_region(&mut region);
+        region
+    }
+
+    fn render(region: &Region, format: ExportFormat) -> String {
+        render_themed(region, format, &Theme::classic())
+    }
+
+    fn render_themed(region: &Region, format: ExportFormat, theme: &Theme) -> String {
+        let mut out = Vec::new();
+        export(region, format, theme, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_text_matches_render_region() {
+        let region = sample();
+        assert_eq!(render(&region, ExportFormat::Text), World::new().render_region(1000, -500, 30, 10));
+    }
+
+    #[test]
+    fn test_ansi_strips_to_text() {
+        let region = sample();
+        let ansi = render(&region, ExportFormat::Ansi);
+
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for c in ansi.chars() {
+            match c {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => {}
+                _ => stripped.push(c),
+            }
+        }
+        assert_eq!(stripped, render(&region, ExportFormat::Text));
+    }
+
+    #[test]
+    fn test_html_and_svg_structure() {
+        let mut region = Region::new(0, 0, 3, 2);
+        region[(1, 0)] = Tile::new('&', Biome::Glitch);
+
+        let html = render(&region, ExportFormat::Html);
+        assert!(html.contains("<span class=\"glitch fg-13\">&amp;</span>"));
+        assert!(html.contains(".fg-11 { color: #ffff55; }"));
+
+        let svg = render(&region, ExportFormat::Svg);
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains("&amp;"));
+    }
+
+    #[test]
+    fn test_styles_follow_tiles() {
+        let mut region = Region::new(0, 0, 4, 1);
+        region[(0, 0)] = Tile::new('~', Biome::Pattern).with_pattern(PatternKind::Wave);
+        region[(1, 0)] = Tile::new('~', Biome::Pattern).with_pattern(PatternKind::Wave);
+        region[(2, 0)] = Tile {
+            bg: Some(Colour::Rgb(4, 5, 6)),
+            ..Tile::new('@', Biome::CatPresent)
+        };
+
+        let ansi = render(&region, ExportFormat::Ansi);
+        assert!(ansi.starts_with("\x1b[0;36m~~\x1b[0;1;93;48;2;4;5;6m@\x1b[0;32m "));
+
+        let html = render(&region, ExportFormat::Html);
+        assert!(html.contains("<span class=\"pattern wave fg-6\">~~</span>"));
+        assert!(html.contains("<span class=\"cat fg-11 bright\" style=\"background: #040506;\">@</span>"));
+
+        // Other themes recolour by biome and pattern, keeping backgrounds and intensity
+        let theme = Theme::solarized().with_biome(Biome::CatPresent, Colour::Rgb(1, 2, 3));
+        let ansi = render_themed(&region, ExportFormat::Ansi, &theme);
+        assert!(ansi.starts_with("\x1b[0;38;2;38;139;210m~~\x1b[0;1;38;2;1;2;3;48;2;4;5;6m@"));
+
+        let html = render_themed(&region, ExportFormat::Html, &theme);
+        assert!(html.contains("body { background: #002b36;"));
+        assert!(html.contains("<span class=\"cat bright\" style=\"color: #010203;background: #040506;\">@</span>"));
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("SVG".parse::<ExportFormat>(), Ok(ExportFormat::Svg));
+        assert_eq!(ExportFormat::Ansi.to_string().parse::<ExportFormat>(), Ok(ExportFormat::Ansi));
+        assert!("png".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Glyph sets for EndlessUtopia
+//! Profiles that swap the world's glyphs for ones a terminal can show, never its layout
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Shown for glyphs a profile has no stand-in for
+const FALLBACK: char = '?';
+
+/// Code page 437 beyond printable ASCII: the graphic glyphs of 0x01-0x1F and 0x7F,
+/// then 0x80-0xFE
+const CP437_EXTRA: &str = "☺☻♥♦♣♠•◘○◙♂♀♪♫☼►◄↕‼¶§▬↨↑↓→←∟↔▲▼⌂\
+    ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»\
+    ░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+    αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■";
+
+/// Which characters the world may use. Generators draw from the full Unicode set;
+/// other profiles replace each glyph they cannot show with a look-alike (blank stays
+/// blank), so every profile shows the same biomes, patterns and creatures in the same places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GlyphSet {
+    /// Box, block and bullet characters as generated
+    #[default]
+    Unicode,
+    /// The IBM PC character set (DOS consoles, many retro fonts)
+    Cp437,
+    /// ISO 8859-1
+    Latin1,
+    /// Strict 7-bit ASCII
+    Ascii,
+}
+
+impl GlyphSet {
+    pub const ALL: [GlyphSet; 4] = [GlyphSet::Unicode, GlyphSet::Cp437, GlyphSet::Latin1, GlyphSet::Ascii];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GlyphSet::Unicode => "unicode",
+            GlyphSet::Cp437 => "cp437",
+            GlyphSet::Latin1 => "latin1",
+            GlyphSet::Ascii => "ascii",
+        }
+    }
+
+    /// Whether the profile can show `c`
+    pub fn contains(&self, c: char) -> bool {
+        let ascii = c == ' ' || c.is_ascii_graphic();
+        match self {
+            GlyphSet::Unicode => !c.is_control(),
+            GlyphSet::Cp437 => ascii || CP437_EXTRA.contains(c),
+            GlyphSet::Latin1 => ascii || ('\u{a1}'..='\u{ff}').contains(&c),
+            GlyphSet::Ascii => ascii,
+        }
+    }
+
+    /// `c` as shown in this profile
+    pub fn glyph(&self, c: char) -> char {
+        if self.contains(c) {
+            return c;
+        }
+        self.substitute(c).filter(|&s| self.contains(s)).unwrap_or(FALLBACK)
+    }
+
+    /// Look-alike for a generated glyph, trying the profile's own table before ASCII.
+    /// Stand-ins are never glyphs the world draws itself, so no two glyphs (and no
+    /// creature) look the same in any profile.
+    fn substitute(&self, c: char) -> Option<char> {
+        let own = match (self, c) {
+            (GlyphSet::Cp437, '▪') => Some('■'),
+            (GlyphSet::Cp437, '▫') => Some('○'),
+            (GlyphSet::Cp437, '¤') => Some('☼'),
+            (GlyphSet::Latin1, '▀') => Some('¯'),
+            (GlyphSet::Latin1, '▪') => Some('×'),
+            (GlyphSet::Latin1, '▫') => Some('°'),
+            _ => None,
+        };
+        own.or(match c {
+            // Trail, from fresh to faded (the last stage, '.', is ASCII already)
+            '•' => Some(':'),
+            '∙' => Some(';'),
+            '·' => Some('\''),
+            '˙' => Some('`'),
+            // Blocks, from dense to light
+            '█' => Some('8'),
+            '▓' => Some('X'),
+            '▒' => Some('!'),
+            '░' => Some('>'),
+            '▀' => Some('"'),
+            '▄' => Some('_'),
+            '▌' => Some('['),
+            '▐' => Some(']'),
+            '▪' => Some('{'),
+            '▫' => Some('}'),
+            '¤' => Some('Q'),
+            // Rivers
+            '≈' => Some('w'),
+            _ => None,
+        })
+    }
+}
+
+impl FromStr for GlyphSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" | "utf8" | "utf-8" => Ok(GlyphSet::Unicode),
+            "cp437" | "dos" => Ok(GlyphSet::Cp437),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(GlyphSet::Latin1),
+            "ascii" => Ok(GlyphSet::Ascii),
+            other => Err(format!("unknown glyph set: {} (expected unicode, cp437, latin1 or ascii)", other)),
+        }
+    }
+}
+
+impl fmt::Display for GlyphSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_contain_their_glyphs() {
+        assert_eq!(CP437_EXTRA.chars().count(), 32 + 127);
+        for set in GlyphSet::ALL {
+            for c in ['•', '∙', '·', '˙', '.', '█', '▓', '▒', '░', '▀', '▄', '▌', '▐', '▪', '▫', '¤', '≈', '@', '~', ' ', '🐱'] {
+                let glyph = set.glyph(c);
+                assert!(set.contains(glyph), "{} shows {:?} as {:?}", set, c, glyph);
+                assert_eq!(glyph == ' ', c == ' ');
+            }
+        }
+        assert_eq!(GlyphSet::Ascii.glyph('▐'), ']');
+        assert_eq!(GlyphSet::Latin1.glyph('▀'), '¯');
+        assert_eq!(GlyphSet::Latin1.glyph('█'), '8');
+        assert_eq!(GlyphSet::Cp437.glyph('▓'), '▓');
+        assert_eq!(GlyphSet::Cp437.glyph('¤'), '☼');
+        assert_eq!(GlyphSet::Cp437.glyph('≈'), '≈');
+        assert_eq!(GlyphSet::Latin1.glyph('≈'), 'w');
+        assert_eq!(GlyphSet::Ascii.glyph('🐱'), FALLBACK);
+        assert_eq!(GlyphSet::Unicode.glyph('🐱'), '🐱');
+    }
+
+    #[test]
+    fn test_stand_ins_keep_glyphs_apart() {
+        // Everything the built-in biomes, trail, creatures and structures draw
+        let mut generated: Vec<char> = "•∙·˙.█▓▒░▀▄▌▐▪▫¤≈ ~/+o*#$%&@Cc,-|=\\Ov^x".chars().collect();
+        generated.extend(crate::structure::INSCRIPTIONS.iter().flat_map(|text| text.chars()));
+        generated.sort();
+        generated.dedup();
+
+        for set in GlyphSet::ALL {
+            let mut shown: Vec<char> = generated.iter().map(|&c| set.glyph(c)).collect();
+            assert!(!shown.contains(&FALLBACK), "{}", set);
+            shown.sort();
+            shown.dedup();
+            assert_eq!(shown.len(), generated.len(), "{} shows two glyphs alike", set);
+        }
+        assert_eq!(GlyphSet::Ascii.glyph('•'), ':');
+    }
+
+    #[test]
+    fn test_parse() {
+        for set in GlyphSet::ALL {
+            assert_eq!(set.to_string().parse(), Ok(set));
+        }
+        assert_eq!("DOS".parse(), Ok(GlyphSet::Cp437));
+        assert!("ebcdic".parse::<GlyphSet>().is_err());
+    }
+}
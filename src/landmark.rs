@@ -0,0 +1,121 @@
+//! Named landmarks for EndlessUtopia
+//! Every structure site is a landmark, named by a syllable grammar seeded from its hash
+
+use std::fmt;
+
+use crate::structure::{Site, SiteKind};
+use crate::world::HASH_MUL;
+
+/// Syllables are an onset, a vowel and a coda; repeats make the common sounds likelier
+const ONSETS: [&str; 24] = [
+    "b", "br", "d", "dr", "f", "g", "gl", "h", "k", "kr", "l", "m", "n", "p", "qu", "r", "s", "sh", "st", "t", "th", "v", "v", "z",
+];
+const VOWELS: [&str; 10] = ["a", "a", "e", "e", "i", "o", "o", "u", "ae", "ei"];
+const CODAS: [&str; 12] = ["", "", "", "l", "n", "r", "r", "s", "th", "nd", "sk", "x"];
+
+/// What a landmark of each kind is called after its name
+fn nouns(kind: SiteKind) -> &'static [&'static str] {
+    match kind {
+        SiteKind::Ruin => &["Ruins", "Keep", "Halls", "Bastion", "Gate"],
+        SiteKind::Monolith => &["Spire", "Stone", "Obelisk", "Needle", "Monument"],
+        SiteKind::Spring => &["Spring", "Well", "Pool", "Source", "Fount"],
+    }
+}
+
+/// A named point of interest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Landmark {
+    pub name: String,
+    pub kind: SiteKind,
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Landmark {
+    /// The landmark at a structure site
+    pub fn at(site: &Site) -> Self {
+        Landmark {
+            name: landmark_name(site.hash, site.kind),
+            kind: site.kind,
+            x: site.x,
+            y: site.y,
+        }
+    }
+
+    /// Whether the landmark is called `name`, ignoring case and extra spaces
+    pub fn is_called(&self, name: &str) -> bool {
+        let mut words = name.split_whitespace();
+        self.name.split(' ').all(|word| words.next().is_some_and(|typed| typed.eq_ignore_ascii_case(word))) && words.next().is_none()
+    }
+}
+
+impl fmt::Display for Landmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {})", self.name, self.x, self.y)
+    }
+}
+
+/// Name for a site: two or three syllables and a noun for its kind,
+/// e.g. "Vorquel Spire" or "Halls of Drathen"
+pub fn landmark_name(hash: u64, kind: SiteKind) -> String {
+    // Each draw mixes the hash again; folding the high bits down keeps the low ones alive
+    let mut h = hash;
+    let mut draw = |count: usize| {
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= h >> 31;
+        ((h >> 32) % count as u64) as usize
+    };
+
+    let syllables = if draw(4) == 0 { 3 } else { 2 };
+    let mut proper = String::new();
+    for _ in 0..syllables {
+        proper.push_str(ONSETS[draw(ONSETS.len())]);
+        proper.push_str(VOWELS[draw(VOWELS.len())]);
+        proper.push_str(CODAS[draw(CODAS.len())]);
+    }
+    proper[..1].make_ascii_uppercase();
+
+    let nouns = nouns(kind);
+    let noun = nouns[draw(nouns.len())];
+    if draw(4) == 0 {
+        format!("{} of {}", noun, proper)
+    } else {
+        format!("{} {}", proper, noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_follow_the_grammar() {
+        let mut names = Vec::new();
+        for i in 0..2000u64 {
+            let kind = SiteKind::ALL[i as usize % 3];
+            let name = landmark_name(i.wrapping_mul(0x9e3779b97f4a7c15), kind);
+            assert_eq!(name, landmark_name(i.wrapping_mul(0x9e3779b97f4a7c15), kind));
+            assert!(name.is_ascii() && name.starts_with(|c: char| c.is_ascii_uppercase()), "{}", name);
+            assert!(nouns(kind).iter().any(|noun| name.starts_with(noun) || name.ends_with(noun)), "{}", name);
+            names.push(name);
+        }
+        names.sort();
+        names.dedup();
+        assert!(names.len() > 1990, "only {} distinct names", names.len());
+    }
+
+    #[test]
+    fn test_is_called() {
+        let landmark = Landmark {
+            name: "Vorquel Spire".to_string(),
+            kind: SiteKind::Monolith,
+            x: 3,
+            y: -4,
+        };
+        assert!(landmark.is_called("Vorquel Spire"));
+        assert!(landmark.is_called("  vorquel   SPIRE "));
+        assert!(!landmark.is_called("Vorquel"));
+        assert!(!landmark.is_called("Vorquel Spire Two"));
+        assert_eq!(landmark.to_string(), "Vorquel Spire (3, -4)");
+    }
+}
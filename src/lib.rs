@@ -1,9 +1,41 @@
-/// EndlessUtopia - A coordinate-based infinite ASCII world generator
-/// Lightweight, deterministic, and WASM-compatible
+//! EndlessUtopia - A coordinate-based infinite ASCII world generator
+//! Lightweight, deterministic, and WASM-compatible
 
 pub mod world;
+pub mod biome;
+pub mod noise;
+pub mod chunk;
+pub mod entity;
+pub mod region;
+pub mod trail;
+pub mod explorer;
+pub mod export;
+pub mod raster;
+pub mod console;
+pub mod session;
+pub mod link;
+pub mod viewport;
+pub mod colour;
+pub mod theme;
+pub mod glyph;
+pub mod structure;
+pub mod landmark;
 
-pub use world::{World, Tile, Biome};
+pub use world::{World, WorldBuilder, Tile, Biome, PatternKind, DEFAULT_SEED};
+pub use colour::Colour;
+pub use theme::{Theme, ThemeError};
+pub use glyph::GlyphSet;
+pub use structure::{Site, SiteKind, StructureLayer};
+pub use landmark::Landmark;
+pub use region::{Rect, Region};
+pub use trail::{Trail, TrailStep};
+pub use explorer::Explorer;
+pub use session::{Session, SessionError};
+pub use link::Link;
+pub use viewport::Viewport;
+pub use biome::{BiomeGenerator, BiomeMode, BiomeRegistry};
+pub use entity::{Behaviour, Creature, Entity, EntityRegistry, Spawn};
+pub use noise::{Noise, NoiseParams};
 
 #[cfg(target_arch = "wasm32")]
 pub mod app;
@@ -0,0 +1,114 @@
+//! Shareable world links for EndlessUtopia
+//! Seed, position and zoom encoded in a URL fragment: `#seed=…&x=…&y=…&z=…`
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::world::seed_from_str;
+
+/// Zoom used when a link does not say
+pub const DEFAULT_ZOOM: f64 = 1.0;
+
+/// A spot in a world: seed (if given), tile position and zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Link {
+    /// `None` keeps whatever world is already open
+    pub seed: Option<u64>,
+    pub x: i64,
+    pub y: i64,
+    pub zoom: f64,
+}
+
+impl Link {
+    pub fn new(seed: Option<u64>, x: i64, y: i64, zoom: f64) -> Self {
+        Link { seed, x, y, zoom }
+    }
+
+    /// Link to tile (x, y) of the world with `seed` at the default zoom
+    pub fn at(seed: u64, x: i64, y: i64) -> Self {
+        Link::new(Some(seed), x, y, DEFAULT_ZOOM)
+    }
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Link::new(None, 0, 0, DEFAULT_ZOOM)
+    }
+}
+
+/// Accepts a bare fragment (`seed=1&x=2`), one with a leading `#`, or a whole URL.
+/// Missing keys keep their defaults and unknown keys are ignored; a text seed
+/// (`seed=bug-1234`) is hashed like `World::from_seed_str`.
+impl FromStr for Link {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fragment = s.split_once('#').map_or(s, |(_, fragment)| fragment);
+        let mut link = Link::default();
+
+        for pair in fragment.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let invalid = || format!("invalid value for {}: {}", key, value);
+            match key {
+                "seed" if value.is_empty() => return Err(invalid()),
+                "seed" => link.seed = Some(value.parse().unwrap_or_else(|_| seed_from_str(value))),
+                "x" => link.x = value.parse().map_err(|_| invalid())?,
+                "y" => link.y = value.parse().map_err(|_| invalid())?,
+                "z" => {
+                    link.zoom = value.parse().map_err(|_| invalid())?;
+                    if !(link.zoom.is_finite() && link.zoom > 0.0) {
+                        return Err(invalid());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(link)
+    }
+}
+
+/// The fragment, including the leading `#`
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("#")?;
+        if let Some(seed) = self.seed {
+            write!(f, "seed={}&", seed)?;
+        }
+        write!(f, "x={}&y={}&z={}", self.x, self.y, self.zoom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let links = [
+            Link::at(crate::world::DEFAULT_SEED, 0, 0),
+            Link::new(Some(u64::MAX), i64::MIN, i64::MAX, 0.125),
+            Link::new(None, -42, 17, 3.5),
+        ];
+        for link in links {
+            assert_eq!(link.to_string().parse::<Link>(), Ok(link));
+        }
+        assert_eq!(Link::at(7, -3, 4).to_string(), "#seed=7&x=-3&y=4&z=1");
+    }
+
+    #[test]
+    fn test_parse_partial_and_full_urls() {
+        let link: Link = "https://example.com/app.html#y=9&x=-2&extra=1".parse().unwrap();
+        assert_eq!(link, Link::new(None, -2, 9, DEFAULT_ZOOM));
+        assert_eq!("".parse::<Link>(), Ok(Link::default()));
+        assert_eq!("seed=bug-1234".parse::<Link>().unwrap().seed, Some(seed_from_str("bug-1234")));
+    }
+
+    #[test]
+    fn test_rejects_bad_values() {
+        assert!("#x=abc".parse::<Link>().is_err());
+        assert!("#y=9223372036854775808".parse::<Link>().is_err());
+        assert!("#z=0".parse::<Link>().is_err());
+        assert!("#z=NaN".parse::<Link>().is_err());
+        assert!("#seed=".parse::<Link>().is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Deterministic gradient noise for EndlessUtopia
+//! Dependency-free 2D gradient noise with fractal octaves, used to grow biome regions
+
+use crate::world::HASH_MUL;
+
+/// Unit gradients for the lattice corners
+const GRADIENTS: [(f64, f64); 8] = [
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+    (std::f64::consts::FRAC_1_SQRT_2, std::f64::consts::FRAC_1_SQRT_2),
+    (-std::f64::consts::FRAC_1_SQRT_2, std::f64::consts::FRAC_1_SQRT_2),
+    (std::f64::consts::FRAC_1_SQRT_2, -std::f64::consts::FRAC_1_SQRT_2),
+    (-std::f64::consts::FRAC_1_SQRT_2, -std::f64::consts::FRAC_1_SQRT_2),
+];
+
+/// Settings for fractal (multi-octave) noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseParams {
+    /// Base frequency in lattice cells per tile (smaller = bigger features)
+    pub frequency: f64,
+    /// Number of layered octaves
+    pub octaves: u32,
+    /// Frequency multiplier between octaves
+    pub lacunarity: f64,
+    /// Amplitude multiplier between octaves
+    pub persistence: f64,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        NoiseParams {
+            frequency: 1.0 / 48.0,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+}
+
+/// Seeded 2D gradient noise field
+#[derive(Debug, Clone, Copy)]
+pub struct Noise {
+    seed: u64,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Noise { seed }
+    }
+
+    /// Single octave of gradient noise in roughly [-1, 1]
+    pub fn sample(&self, x: f64, y: f64) -> f64 {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (ix, iy) = (x0 as i64, y0 as i64);
+
+        let n00 = self.corner(ix, iy, fx, fy);
+        let n10 = self.corner(ix.wrapping_add(1), iy, fx - 1.0, fy);
+        let n01 = self.corner(ix, iy.wrapping_add(1), fx, fy - 1.0);
+        let n11 = self.corner(ix.wrapping_add(1), iy.wrapping_add(1), fx - 1.0, fy - 1.0);
+
+        let u = fade(fx);
+        let v = fade(fy);
+        let top = lerp(n00, n10, u);
+        let bottom = lerp(n01, n11, u);
+
+        // 2D gradient noise peaks at +-sqrt(0.5); stretch to +-1
+        (lerp(top, bottom, v) * std::f64::consts::SQRT_2).clamp(-1.0, 1.0)
+    }
+
+    /// Fractal sum of octaves, normalized to roughly [-1, 1]
+    pub fn fractal(&self, x: f64, y: f64, params: &NoiseParams) -> f64 {
+        let mut frequency = params.frequency;
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut norm = 0.0;
+
+        for octave in 0..params.octaves.max(1) {
+            // Offset each octave so lattice points don't line up
+            let offset = octave as f64 * 17.13;
+            sum += self.sample(x * frequency + offset, y * frequency - offset) * amplitude;
+            norm += amplitude;
+            frequency *= params.lacunarity;
+            amplitude *= params.persistence;
+        }
+
+        sum / norm
+    }
+
+    /// Dot product of the corner gradient with the offset to it
+    fn corner(&self, ix: i64, iy: i64, dx: f64, dy: f64) -> f64 {
+        let mut h = self.seed;
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= ix as u64;
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= iy as u64;
+        h = h.wrapping_mul(HASH_MUL);
+        let (gx, gy) = GRADIENTS[((h >> 32) % GRADIENTS.len() as u64) as usize];
+        gx * dx + gy * dy
+    }
+}
+
+/// Spread fractal noise (roughly normal, sd ~0.2) evenly over [0, 1).
+/// Used so biome weights keep their meaning when picked from noise.
+pub fn equalize(value: f64) -> f64 {
+    let z = value / (0.2 * std::f64::consts::SQRT_2);
+    (0.5 * (1.0 + erf(z))).clamp(0.0, 1.0 - f64::EPSILON)
+}
+
+/// Tile coordinate as an f64 for noise and wave math. Wrapped into +-2^47 so the
+/// fraction keeps its precision: unchanged near the origin, repeating every 2^48 tiles.
+pub fn tile_coord(coord: i64) -> f64 {
+    ((coord << 16) >> 16) as f64
+}
+
+/// Error function approximation (Abramowitz & Stegun 7.1.26)
+fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+/// Quintic smoothstep
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_deterministic() {
+        let a = Noise::new(7);
+        let b = Noise::new(7);
+        let params = NoiseParams::default();
+
+        assert_eq!(a.fractal(12.5, -3.25, &params), b.fractal(12.5, -3.25, &params));
+        assert_ne!(a.sample(0.5, 0.5), Noise::new(8).sample(0.5, 0.5));
+    }
+
+    #[test]
+    fn test_noise_range_and_lattice_zero() {
+        let noise = Noise::new(42);
+        let params = NoiseParams::default();
+
+        for i in 0..2000 {
+            let x = i as f64 * 0.731 - 500.0;
+            let y = i as f64 * 1.377 + 250.0;
+            let v = noise.fractal(x, y, &params);
+            assert!((-1.0..=1.0).contains(&v));
+        }
+
+        // Gradient noise is zero on lattice points
+        assert_eq!(noise.sample(3.0, -4.0), 0.0);
+    }
+
+    #[test]
+    fn test_equalize() {
+        assert!((equalize(0.0) - 0.5).abs() < 1e-6);
+        assert!(equalize(-1.0) < 0.01);
+        assert!(equalize(1.0) > 0.99 && equalize(1.0) < 1.0);
+        assert!(equalize(0.1) > equalize(0.05));
+    }
+
+    #[test]
+    fn test_tile_coord_wraps_far_tiles() {
+        assert_eq!(tile_coord(-1234), -1234.0);
+        assert_eq!(tile_coord((1 << 47) - 1), ((1u64 << 47) - 1) as f64);
+        assert_eq!(tile_coord(1 << 47), -((1u64 << 47) as f64));
+        assert_eq!(tile_coord(i64::MAX), -1.0);
+        assert_eq!(tile_coord(i64::MIN + 5), 5.0);
+    }
+
+    #[test]
+    fn test_noise_is_continuous() {
+        let noise = Noise::new(3);
+        let params = NoiseParams::default();
+
+        for i in 0..500 {
+            let x = i as f64 - 250.0;
+            let a = noise.fractal(x, 10.0, &params);
+            let b = noise.fractal(x + 1.0, 10.0, &params);
+            assert!((a - b).abs() < 0.25);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Raster biome maps for EndlessUtopia
+//! One pixel per tile, coloured by biome in a theme; binary PPM always, PNG with the `png` feature
+
+use std::io::{self, Write};
+
+use crate::chunk::{ChunkCoord, CHUNK_SIZE};
+use crate::region::Region;
+use crate::theme::{Role, Theme};
+use crate::world::{Biome, World};
+
+/// Map colour of a biome in `theme`. Calm tiles are mostly blank, so they are drawn
+/// a quarter of the way from the background to their colour.
+pub fn biome_rgb(theme: &Theme, biome: Biome) -> [u8; 3] {
+    let colour = theme.biome(biome).rgb();
+    match biome {
+        Biome::Calm => {
+            let background = theme.ui(Role::Background).rgb();
+            std::array::from_fn(|i| ((background[i] as u16 * 3 + colour[i] as u16) / 4) as u8)
+        }
+        _ => colour,
+    }
+}
+
+/// Tiles sampled per axis by `chunk_rgb`
+pub const CHUNK_SAMPLES: usize = 4;
+
+/// Average biome colour of a chunk, from a CHUNK_SAMPLES x CHUNK_SAMPLES grid of
+/// base tiles. Cheap enough to colour thousands of chunks in a zoomed-out view.
+pub fn chunk_rgb(world: &World, theme: &Theme, chunk: ChunkCoord) -> [u8; 3] {
+    let (origin_x, origin_y) = chunk.origin();
+    let step = CHUNK_SIZE / CHUNK_SAMPLES;
+    let mut sum = [0u32; 3];
+    for sample_y in 0..CHUNK_SAMPLES {
+        for sample_x in 0..CHUNK_SAMPLES {
+            let x = origin_x.wrapping_add((sample_x * step + step / 2) as i64);
+            let y = origin_y.wrapping_add((sample_y * step + step / 2) as i64);
+            for (total, channel) in sum.iter_mut().zip(biome_rgb(theme, world.base_tile(x, y).biome)) {
+                *total += channel as u32;
+            }
+        }
+    }
+    let samples = (CHUNK_SAMPLES * CHUNK_SAMPLES) as u32;
+    sum.map(|total| (total / samples) as u8)
+}
+
+/// Generate a large area band by band (one chunk row at a time) so memory stays
+/// bounded; `f` receives the pixel bytes (RGB) of each row in order
+pub fn for_each_pixel_row(
+    world: &World,
+    theme: &Theme,
+    x: i64,
+    y: i64,
+    width: usize,
+    height: usize,
+    mut f: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut band = Region::new(x, y, width, 0);
+    let mut pixels = Vec::with_capacity(width * 3);
+
+    let mut band_y = 0;
+    while band_y < height {
+        let rows = CHUNK_SIZE.min(height - band_y);
+        band.set_bounds(x, y.wrapping_add(band_y as i64), width, rows);
+        world.fill_region(&mut band);
+
+        for row in band.rows() {
+            pixels.clear();
+            pixels.extend(row.iter().flat_map(|tile| biome_rgb(theme, tile.biome)));
+            f(&pixels)?;
+        }
+        band_y += rows;
+    }
+
+    Ok(())
+}
+
+/// Write a binary PPM (P6) biome map
+pub fn write_ppm(world: &World, theme: &Theme, x: i64, y: i64, width: usize, height: usize, out: &mut impl Write) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    for_each_pixel_row(world, theme, x, y, width, height, |pixels| out.write_all(pixels))
+}
+
+/// Write a PNG biome map
+#[cfg(feature = "png")]
+pub fn write_png(world: &World, theme: &Theme, x: i64, y: i64, width: usize, height: usize, out: &mut impl Write) -> io::Result<()> {
+    let size = |pixels: usize| {
+        u32::try_from(pixels).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("png size {} is over {} pixels", pixels, u32::MAX)))
+    };
+    let mut encoder = png::Encoder::new(out, size(width)?, size(height)?);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    let mut stream = writer.stream_writer().map_err(io::Error::other)?;
+    for_each_pixel_row(world, theme, x, y, width, height, |pixels| stream.write_all(pixels))?;
+    stream.finish().map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppm_layout() {
+        let world = World::new();
+        let mut out = Vec::new();
+        let theme = Theme::classic();
+        write_ppm(&world, &theme, -10, -10, 50, 70, &mut out).unwrap();
+
+        let header = b"P6\n50 70\n255\n";
+        assert!(out.starts_with(header));
+        assert_eq!(out.len(), header.len() + 50 * 70 * 3);
+
+        // Pixel (3, 40) matches the tile's biome colour
+        let offset = header.len() + (40 * 50 + 3) * 3;
+        let expected = biome_rgb(&theme, world.get_tile(-7, 30).biome);
+        assert_eq!(&out[offset..offset + 3], &expected);
+    }
+
+    #[test]
+    fn test_cat_pixel() {
+        let world = World::new();
+        let (cat_x, cat_y) = world.ascicat_position();
+        let theme = Theme::amber();
+        let mut rows = Vec::new();
+        for_each_pixel_row(&world, &theme, cat_x - 1, cat_y - 1, 3, 3, |pixels| {
+            rows.push(pixels.to_vec());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(&rows[1][3..6], &theme.biome(Biome::CatPresent).rgb());
+    }
+
+    #[test]
+    fn test_chunk_rgb_averages_biomes() {
+        let world = World::new();
+        let chunk = ChunkCoord { x: -3, y: 7 };
+        let (origin_x, origin_y) = chunk.origin();
+
+        // The average of the sampled tiles, channel by channel
+        let mut expected = [0u32; 3];
+        for y in [4, 12, 20, 28] {
+            for x in [4, 12, 20, 28] {
+                let rgb = biome_rgb(&Theme::classic(), world.base_tile(origin_x + x, origin_y + y).biome);
+                for i in 0..3 {
+                    expected[i] += rgb[i] as u32;
+                }
+            }
+        }
+        assert_eq!(chunk_rgb(&world, &Theme::classic(), chunk), expected.map(|total| (total / 16) as u8));
+
+        // A chunk of a single biome keeps its colour in every theme
+        let calm = World::builder().without_default_biomes().biome(crate::biome::CalmBiome).build();
+        for theme in Theme::builtin() {
+            assert_eq!(chunk_rgb(&calm, &theme, chunk), biome_rgb(&theme, Biome::Calm));
+        }
+        assert_ne!(biome_rgb(&Theme::classic(), Biome::Calm), biome_rgb(&Theme::solarized(), Biome::Calm));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_round_trip() {
+        let world = World::new();
+        let mut out = Vec::new();
+        write_png(&world, &Theme::classic(), 0, 0, 40, 33, &mut out).unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(out));
+        let reader = decoder.read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (40, 33));
+
+        // Sizes the header cannot hold are refused before anything is written
+        if let Some(huge) = (u32::MAX as usize).checked_add(1) {
+            let mut out = Vec::new();
+            let err = write_png(&world, &Theme::classic(), 0, 0, huge, 1, &mut out).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(out.is_empty());
+        }
+    }
+}
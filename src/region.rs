@@ -0,0 +1,231 @@
+//! Flat rectangular tile buffers for EndlessUtopia
+//! A `Region` is filled by `World::fill_region` and can be reused between frames
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::world::Tile;
+
+/// A rectangle of world tiles (no storage); `x`/`y` is the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i64,
+    pub y: i64,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: i64, y: i64, width: usize, height: usize) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    /// Square of side 2 * radius + 1 around (x, y)
+    pub fn around(x: i64, y: i64, radius: u32) -> Self {
+        let side = 2 * radius as usize + 1;
+        Rect::new(x.saturating_sub_unsigned(radius as u64), y.saturating_sub_unsigned(radius as u64), side, side)
+    }
+
+    /// The rectangle extended by `by` tiles on every side
+    pub fn grow(&self, by: u32) -> Self {
+        let x = self.x.saturating_sub_unsigned(by as u64);
+        let y = self.y.saturating_sub_unsigned(by as u64);
+        let grow = |start: i64, length: usize, new_start: i64| {
+            (start.abs_diff(new_start) as usize).saturating_add(length).saturating_add(by as usize)
+        };
+        Rect::new(x, y, grow(self.x, self.width, x), grow(self.y, self.height, y))
+    }
+
+    /// Whether world tile (x, y) lies inside
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        let dx = x as i128 - self.x as i128;
+        let dy = y as i128 - self.y as i128;
+        dx >= 0 && dy >= 0 && dx < self.width as i128 && dy < self.height as i128
+    }
+}
+
+/// A rectangle of tiles stored in one contiguous, row-major buffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    x: i64,
+    y: i64,
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Region {
+    /// Create a region of blank tiles with its top-left corner at world (x, y)
+    pub fn new(x: i64, y: i64, width: usize, height: usize) -> Self {
+        Region {
+            x,
+            y,
+            width,
+            height,
+            tiles: vec![Tile::default(); width * height],
+        }
+    }
+
+    /// Move and resize the region, keeping the allocation when it is big enough.
+    /// Tile contents are unspecified until the region is filled again.
+    pub fn set_bounds(&mut self, x: i64, y: i64, width: usize, height: usize) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+        self.tiles.resize(width * height, Tile::default());
+    }
+
+    /// World coordinates of the top-left tile
+    pub fn origin(&self) -> (i64, i64) {
+        (self.x, self.y)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Tile at region-local (x, y)
+    pub fn get(&self, x: usize, y: usize) -> Option<&Tile> {
+        if x < self.width && y < self.height {
+            Some(&self.tiles[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// One row of tiles
+    pub fn row(&self, y: usize) -> &[Tile] {
+        &self.tiles[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [Tile] {
+        &mut self.tiles[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Iterate rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[Tile]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    /// All tiles, row-major
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.tiles
+    }
+
+    /// The world rectangle this region covers
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
+
+    /// Region-local position of world tile (x, y), if it lies inside
+    pub fn local(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        if self.rect().contains(x, y) {
+            Some((x.abs_diff(self.x) as usize, y.abs_diff(self.y) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Copy into the nested layout returned by `World::get_region`
+    pub fn to_rows(&self) -> Vec<Vec<Tile>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+}
+
+impl Index<(usize, usize)> for Region {
+    type Output = Tile;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Tile {
+        assert!(x < self.width && y < self.height, "tile ({}, {}) outside region", x, y);
+        &self.tiles[y * self.width + x]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Region {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Tile {
+        assert!(x < self.width && y < self.height, "tile ({}, {}) outside region", x, y);
+        &mut self.tiles[y * self.width + x]
+    }
+}
+
+/// Renders one line per row, each terminated by '\n'
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        for row in self.rows() {
+            for tile in row {
+                f.write_char(tile.character)?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Biome;
+
+    #[test]
+    fn test_indexing_and_rows() {
+        let mut region = Region::new(10, -5, 3, 2);
+        region[(2, 1)] = Tile::new('#', Biome::Glitch);
+
+        assert_eq!(region.rows().count(), 2);
+        assert_eq!(region.row(1)[2].character, '#');
+        assert_eq!(region.get(2, 1).unwrap().biome, Biome::Glitch);
+        assert!(region.get(3, 0).is_none());
+        assert_eq!(region.local(12, -4), Some((2, 1)));
+        assert_eq!(region.local(13, -4), None);
+        assert_eq!(region.to_string(), "   \n  #\n");
+    }
+
+    #[test]
+    fn test_zero_width_keeps_rows() {
+        let region = Region::new(0, 0, 0, 3);
+        assert_eq!(region.rows().count(), 3);
+        assert_eq!(region.to_string(), "\n\n\n");
+
+        let world = crate::world::World::new();
+        assert_eq!(world.get_region(0, 0, 0, 3), vec![Vec::<Tile>::new(); 3]);
+        assert_eq!(world.render_region(0, 0, 0, 3), "\n\n\n");
+    }
+
+    #[test]
+    fn test_set_bounds_reuses_buffer() {
+        let mut region = Region::new(0, 0, 40, 20);
+        let capacity = region.tiles.capacity();
+        region.set_bounds(5, 5, 20, 10);
+
+        assert_eq!(region.tiles().len(), 200);
+        assert_eq!(region.tiles.capacity(), capacity);
+        assert_eq!(region.origin(), (5, 5));
+    }
+
+    #[test]
+    fn test_rect_contains() {
+        let rect = Rect::around(0, 0, 2);
+        assert_eq!(rect, Rect::new(-2, -2, 5, 5));
+        assert!(rect.contains(-2, 2));
+        assert!(!rect.contains(3, 0));
+
+        let edge = Rect::new(i64::MAX - 1, 0, 10, 1);
+        assert!(edge.contains(i64::MAX, 0));
+        assert!(!edge.contains(i64::MIN, 0));
+        assert_eq!(Region::new(i64::MAX - 1, 0, 10, 1).rect(), edge);
+        assert_eq!(rect.grow(3), Rect::new(-5, -5, 11, 11));
+        assert_eq!(Rect::new(i64::MIN + 1, 0, 2, 2).grow(4), Rect::new(i64::MIN, -4, 7, 10));
+        assert_eq!(Region::new(i64::MIN, i64::MAX - 2, 4, 3).local(i64::MIN + 3, i64::MAX), Some((3, 2)));
+    }
+}
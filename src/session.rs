@@ -0,0 +1,363 @@
+//! Saved exploration sessions for EndlessUtopia
+//! A small, versioned, line-based text format: one `key value` pair per line
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::explorer::Explorer;
+use crate::trail::Trail;
+use crate::viewport::{WorldPoint, DEFAULT_TILE_HEIGHT, DEFAULT_TILE_WIDTH};
+use crate::world::{WorldBuilder, DEFAULT_SEED};
+
+/// First line of every session file, followed by the format version
+pub const SESSION_HEADER: &str = "endless-utopia-session";
+
+/// Format version written by `Session::save`.
+/// Version 1 stored the view and drawings in browser pixels, version 2 in tiles (as f64),
+/// version 3 as a whole tile plus an offset, e.g. `-1235+0.5`, to stay exact far out.
+pub const SESSION_VERSION: u32 = 3;
+
+/// Oldest format version `Session::load` still reads (and migrates)
+pub const MIN_SESSION_VERSION: u32 = 1;
+
+/// Why a session could not be loaded
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    /// Not a session file (missing or wrong header)
+    NotASession,
+    /// Written by a newer (or unknown) version of the format
+    UnsupportedVersion(u32),
+    /// A line that could not be parsed (1-based line number)
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "could not read session: {}", err),
+            SessionError::NotASession => write!(f, "not a session file"),
+            SessionError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported session version {} (expected {} to {})",
+                version, MIN_SESSION_VERSION, SESSION_VERSION
+            ),
+            SessionError::Malformed { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(err: io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
+
+/// Everything a user builds up while exploring: the world's seed, the current tick
+/// and cat trail, the viewport, freehand drawings and the command history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub seed: u64,
+    pub tick: u64,
+    /// Centre of the viewport
+    pub view: WorldPoint,
+    pub trail: Trail,
+    /// Freehand strokes, each a list of world points
+    pub drawings: Vec<Vec<WorldPoint>>,
+    pub history: Vec<String>,
+}
+
+impl Session {
+    /// An empty session for the world with `seed`
+    pub fn new(seed: u64) -> Self {
+        Session {
+            seed,
+            tick: 0,
+            view: WorldPoint::default(),
+            trail: Trail::new(),
+            drawings: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Seed, tick and trail of an explorer; the frontend fills in the rest
+    pub fn from_explorer(explorer: &Explorer) -> Self {
+        Session {
+            tick: explorer.tick(),
+            trail: explorer.trail().clone(),
+            ..Session::new(explorer.world().seed())
+        }
+    }
+
+    /// Explorer for this session's world (built from `builder` with the saved seed),
+    /// at the saved tick and with the saved trail
+    pub fn explorer(&self, builder: WorldBuilder) -> Explorer {
+        let mut explorer = Explorer::new(builder.seed(self.seed).build());
+        explorer.set_trail(self.trail.clone());
+        explorer.set_tick(self.tick);
+        explorer
+    }
+
+    /// Write the session in the current format version
+    pub fn save(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{} {}", SESSION_HEADER, SESSION_VERSION)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "tick {}", self.tick)?;
+        writeln!(out, "view {} {}", coord(self.view.tile_x, self.view.offset_x), coord(self.view.tile_y, self.view.offset_y))?;
+        for step in self.trail.iter() {
+            writeln!(out, "step {} {} {}", step.x, step.y, step.tick)?;
+        }
+        for drawing in &self.drawings {
+            write!(out, "drawing")?;
+            for point in drawing {
+                write!(out, " {},{}", coord(point.tile_x, point.offset_x), coord(point.tile_y, point.offset_y))?;
+            }
+            writeln!(out)?;
+        }
+        for command in &self.history {
+            writeln!(out, "history {}", escape(command))?;
+        }
+        Ok(())
+    }
+
+    /// Read a session written by this or an older format version
+    pub fn load(input: impl Read) -> Result<Self, SessionError> {
+        let mut lines = BufReader::new(input).lines();
+        let header = lines.next().transpose()?.ok_or(SessionError::NotASession)?;
+        let version = match header.split_once(' ') {
+            Some((SESSION_HEADER, version)) => version.trim().parse().map_err(|_| SessionError::NotASession)?,
+            _ => return Err(SessionError::NotASession),
+        };
+        if !(MIN_SESSION_VERSION..=SESSION_VERSION).contains(&version) {
+            return Err(SessionError::UnsupportedVersion(version));
+        }
+
+        let mut session = Session::new(DEFAULT_SEED);
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            session
+                .read_line(&line)
+                .map_err(|message| SessionError::Malformed { line: index + 2, message })?;
+        }
+        session.migrate(version)
+    }
+
+    /// Apply one `key value` line
+    fn read_line(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "" => {}
+            "seed" => self.seed = parse(value)?,
+            "tick" => self.tick = parse(value)?,
+            "view" => self.view = parse_point(value, ' ')?,
+            "step" => {
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                let [x, y, tick] = parts[..] else {
+                    return Err(format!("expected 'step <x> <y> <tick>', got '{}'", value));
+                };
+                self.trail.record(parse(x)?, parse(y)?, parse(tick)?);
+            }
+            "drawing" => {
+                let points = value.split_whitespace().map(|point| parse_point(point, ',')).collect::<Result<_, _>>()?;
+                self.drawings.push(points);
+            }
+            "history" => self.history.push(unescape(value)),
+            other => return Err(format!("unknown key '{}'", other)),
+        }
+        Ok(())
+    }
+
+    /// Bring a session read in format `version` up to date, one version at a time.
+    /// Add a step here whenever `SESSION_VERSION` is bumped.
+    fn migrate(mut self, mut version: u32) -> Result<Self, SessionError> {
+        while version < SESSION_VERSION {
+            match version {
+                1 => {
+                    // Pixels of the browser canvas at zoom 1 to tiles
+                    let to_tiles = |point: WorldPoint| {
+                        let (x, y) = point.to_f64();
+                        WorldPoint::from_f64(x / DEFAULT_TILE_WIDTH, y / DEFAULT_TILE_HEIGHT)
+                    };
+                    self.view = to_tiles(self.view);
+                    for point in self.drawings.iter_mut().flatten() {
+                        *point = to_tiles(*point);
+                    }
+                }
+                2 => {
+                    // Plain numbers still read as points, rounded as version 2 stored them;
+                    // saving again writes them as whole tiles and offsets
+                }
+                other => return Err(SessionError::UnsupportedVersion(other)),
+            }
+            version += 1;
+        }
+        Ok(self)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("invalid number '{}'", value.trim()))
+}
+
+fn parse_point(value: &str, separator: char) -> Result<WorldPoint, String> {
+    let (x, y) = value
+        .trim()
+        .split_once(separator)
+        .ok_or_else(|| format!("expected two numbers, got '{}'", value))?;
+    let ((tile_x, offset_x), (tile_y, offset_y)) = (parse_coord(x)?, parse_coord(y)?);
+    Ok(WorldPoint { tile_x, tile_y, offset_x, offset_y }.normalised())
+}
+
+/// A coordinate as `tile+offset`, or just `tile` on a tile's edge
+fn coord(tile: i64, offset: f64) -> String {
+    if offset == 0.0 {
+        tile.to_string()
+    } else {
+        format!("{}+{}", tile, offset)
+    }
+}
+
+/// Reads `coord`'s output, and the plain numbers older versions wrote
+fn parse_coord(value: &str) -> Result<(i64, f64), String> {
+    let value = value.trim();
+    if let Some((tile, offset)) = value.split_once('+') {
+        return Ok((parse(tile)?, parse(offset)?));
+    }
+    if let Ok(tile) = value.parse() {
+        return Ok((tile, 0.0));
+    }
+    let point = WorldPoint::from_f64(parse(value)?, 0.0);
+    Ok((point.tile_x, point.offset_x))
+}
+
+/// Keep a command on one line
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    fn sample() -> Session {
+        let mut explorer = Explorer::new(World::with_seed(42));
+        explorer.set_tick(300);
+        let mut session = Session::from_explorer(&explorer);
+        session.view = WorldPoint::from_f64(-1234.5, 0.1);
+        session.drawings = vec![vec![WorldPoint::from_f64(1.0, 2.0), WorldPoint::from_f64(3.25, -4.0)], vec![]];
+        session.history = vec!["goto 5 5".to_string(), "say \"a\\b\"\nsecond line".to_string()];
+        session
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let session = sample();
+        let mut bytes = Vec::new();
+        session.save(&mut bytes).unwrap();
+        assert!(bytes.starts_with(format!("{} {}\n", SESSION_HEADER, SESSION_VERSION).as_bytes()));
+
+        let loaded = Session::load(&bytes[..]).unwrap();
+        assert_eq!(loaded, session);
+        assert!(!loaded.trail.is_empty());
+
+        let explorer = loaded.explorer(World::builder());
+        assert_eq!(explorer.world().seed(), 42);
+        assert_eq!(explorer.tick(), 300);
+        assert_eq!(explorer.trail(), &session.trail);
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let newer = format!("{} {}\nseed 1\n", SESSION_HEADER, SESSION_VERSION + 1);
+        assert!(matches!(
+            Session::load(newer.as_bytes()),
+            Err(SessionError::UnsupportedVersion(version)) if version == SESSION_VERSION + 1
+        ));
+        assert!(matches!(Session::load("endless-utopia-session 0\n".as_bytes()), Err(SessionError::UnsupportedVersion(0))));
+        assert!(matches!(Session::load("hello\n".as_bytes()), Err(SessionError::NotASession)));
+        assert!(matches!(Session::load(&b""[..]), Err(SessionError::NotASession)));
+    }
+
+    #[test]
+    fn test_migrates_pixel_sessions() {
+        let old = "endless-utopia-session 1\nseed 9\nview 720 -160\ndrawing 7.2,16 0,8\nhistory goto 720 160\n";
+        let session = Session::load(old.as_bytes()).unwrap();
+        assert_eq!(session.seed, 9);
+        assert_eq!(session.view, WorldPoint::from_f64(100.0, -10.0));
+        assert_eq!(session.drawings, vec![vec![WorldPoint::from_f64(1.0, 1.0), WorldPoint::from_f64(0.0, 0.5)]]);
+        assert_eq!(session.history, vec!["goto 720 160"]);
+
+        // Saving writes the current version, which loads unchanged
+        let mut bytes = Vec::new();
+        session.save(&mut bytes).unwrap();
+        assert_eq!(Session::load(&bytes[..]).unwrap(), session);
+    }
+
+    #[test]
+    fn test_exact_far_from_origin() {
+        let far = 1i64 << 60;
+        let mut session = sample();
+        session.view = WorldPoint {
+            tile_x: far + 3,
+            tile_y: -far - 7,
+            offset_x: 0.25,
+            offset_y: 0.75,
+        };
+        session.drawings = vec![vec![session.view, WorldPoint { tile_x: -far, tile_y: far, offset_x: 0.0, offset_y: 0.5 }]];
+
+        let mut bytes = Vec::new();
+        session.save(&mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.contains("view 1152921504606846979+0.25 -1152921504606846983+0.75\n"), "{}", text);
+        assert_eq!(Session::load(text.as_bytes()).unwrap(), session);
+
+        // Version 2 stored plain f64s, which migrate to the tiles they rounded to
+        let old = "endless-utopia-session 2\nview 1152921504606846976 -2.5\ndrawing 1.5,-0.25\n";
+        let session = Session::load(old.as_bytes()).unwrap();
+        assert_eq!(session.view, WorldPoint { tile_x: far, tile_y: -3, offset_x: 0.0, offset_y: 0.5 });
+        assert_eq!(session.drawings, vec![vec![WorldPoint { tile_x: 1, tile_y: -1, offset_x: 0.5, offset_y: 0.75 }]]);
+    }
+
+    #[test]
+    fn test_reports_malformed_lines() {
+        let input = "endless-utopia-session 1\nseed 7\nview 1 x\n";
+        match Session::load(input.as_bytes()) {
+            Err(SessionError::Malformed { line, message }) => {
+                assert_eq!(line, 3);
+                assert!(message.contains("'x'"));
+            }
+            other => panic!("expected a malformed line, got {:?}", other),
+        }
+        assert!(Session::load("endless-utopia-session 1\ncolour red\n".as_bytes()).is_err());
+
+        // Missing keys keep their defaults
+        let minimal = Session::load("endless-utopia-session 1\nseed 7\n".as_bytes()).unwrap();
+        assert_eq!(minimal, Session::new(7));
+    }
+}
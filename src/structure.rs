@@ -0,0 +1,457 @@
+//! Structure layer for EndlessUtopia
+//! Ruins, monoliths, rivers and roads placed per chunk, drawn lazily one tile at a time
+
+use crate::chunk::{ChunkCoord, CHUNK_SIZE};
+use crate::noise::{self, Noise, NoiseParams};
+use crate::region::Rect;
+use crate::world::{Biome, PatternKind, Tile, HASH_MUL};
+
+/// One chunk in this many holds a site
+const SITE_CHANCE: u64 = 3;
+
+/// Sites keep this many tiles away from the edges of their chunk
+const SITE_MARGIN: i64 = 6;
+
+/// A river runs this many straight segments of `RIVER_STEP` tiles from its spring.
+/// Their 36 tiles never reach past the chunks next to the spring's, which start
+/// `CHUNK_SIZE + SITE_MARGIN` tiles away - so each tile only checks the 3x3 chunks around it.
+const RIVER_SEGMENTS: usize = 4;
+const RIVER_STEP: f64 = 9.0;
+
+/// Chunk offsets a site's roads may head to; the opposite directions are
+/// the roads of the neighbours, so every pair of sites is considered once
+const ROAD_DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
+/// Percentage of neighbouring sites joined by a road
+const ROAD_CHANCE: u64 = 60;
+
+/// Texts carved into monoliths
+pub(crate) const INSCRIPTIONS: [&str; 10] = ["UTOPIA", "NO END", "MEOW", "HERE", "ONWARD", "LOOK UP", "0x2A", "REMEMBER", "ALL IS WELL", "KEEP WALKING"];
+
+/// Height field the rivers run down
+const TERRAIN: NoiseParams = NoiseParams {
+    frequency: 1.0 / 96.0,
+    octaves: 1,
+    lacunarity: 2.0,
+    persistence: 0.5,
+};
+
+/// What stands at a site
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SiteKind {
+    /// Crumbling walls around an empty floor
+    Ruin,
+    /// A slab with an inscription
+    Monolith,
+    /// A pond that a river flows out of
+    Spring,
+}
+
+impl SiteKind {
+    pub const ALL: [SiteKind; 3] = [SiteKind::Ruin, SiteKind::Monolith, SiteKind::Spring];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SiteKind::Ruin => "ruin",
+            SiteKind::Monolith => "monolith",
+            SiteKind::Spring => "spring",
+        }
+    }
+}
+
+/// A structure anchored at one tile; roads run between sites
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Site {
+    pub x: i64,
+    pub y: i64,
+    pub kind: SiteKind,
+    /// Seeds the structure's shape
+    pub hash: u64,
+}
+
+/// Deterministic multi-tile features on top of the biomes.
+/// At most one site per chunk, so any tile only has to look at a fixed
+/// neighbourhood of chunks and `tile_at` stays O(1).
+#[derive(Debug, Clone)]
+pub struct StructureLayer {
+    seed: u64,
+    terrain: Noise,
+}
+
+impl StructureLayer {
+    pub fn new(seed: u64) -> Self {
+        StructureLayer {
+            seed,
+            // Independent of the site hashes, which mix the same seed with chunk coordinates
+            terrain: Noise::new(!seed),
+        }
+    }
+
+    /// The site of a chunk, if it has one
+    pub fn site_in(&self, chunk: ChunkCoord) -> Option<Site> {
+        let hash = mix(self.seed, chunk.x, chunk.y);
+        if !(hash >> 32).is_multiple_of(SITE_CHANCE) {
+            return None;
+        }
+
+        let kind = match (hash >> 36) % 9 {
+            0..=3 => SiteKind::Ruin,
+            4..=5 => SiteKind::Monolith,
+            _ => SiteKind::Spring,
+        };
+        let span = (CHUNK_SIZE as i64 - 2 * SITE_MARGIN) as u64;
+        let (ox, oy) = chunk.origin();
+        Some(Site {
+            x: ox.wrapping_add(SITE_MARGIN + ((hash >> 40) % span) as i64),
+            y: oy.wrapping_add(SITE_MARGIN + ((hash >> 48) % span) as i64),
+            kind,
+            hash,
+        })
+    }
+
+    /// Every site inside `rect`, row of chunks by row of chunks
+    pub fn sites_in(&self, rect: Rect) -> Vec<Site> {
+        let mut sites = Vec::new();
+        if rect.width == 0 || rect.height == 0 {
+            return sites;
+        }
+
+        let chunks = |start: i64, length: usize| {
+            let last = start.saturating_add_unsigned(length as u64 - 1);
+            start.div_euclid(CHUNK_SIZE as i64)..=last.div_euclid(CHUNK_SIZE as i64)
+        };
+        for y in chunks(rect.y, rect.height) {
+            for x in chunks(rect.x, rect.width) {
+                if let Some(site) = self.site_in(ChunkCoord { x, y }) {
+                    if rect.contains(site.x, site.y) {
+                        sites.push(site);
+                    }
+                }
+            }
+        }
+        sites
+    }
+
+    /// Structure tile at (x, y), or `None` where the biome shows through
+    pub fn tile_at(&self, x: i64, y: i64) -> Option<Tile> {
+        let nearby = Nearby::new(self, ChunkCoord::containing(x, y));
+
+        // Ruins and monoliths stay within a few tiles of their site
+        for site in nearby.sites() {
+            if let Some(tile) = self.site_tile(&site, x, y) {
+                return Some(tile);
+            }
+        }
+
+        let river = nearby
+            .sites()
+            .filter(|site| site.kind == SiteKind::Spring)
+            .any(|spring| self.on_river(&spring, x.wrapping_sub(spring.x), y.wrapping_sub(spring.y)));
+        match (river, self.road_glyph(&nearby, x, y)) {
+            (true, Some(_)) => Some(structure_tile('=', PatternKind::Road)),
+            (true, None) => Some(structure_tile('≈', PatternKind::River)),
+            (false, Some(glyph)) => Some(structure_tile(glyph, PatternKind::Road)),
+            (false, None) => None,
+        }
+    }
+
+    /// Tile offsets from a spring of the points its river bends at, starting with the spring.
+    /// Each segment heads straight down the terrain's slope where it starts.
+    pub fn river_course(&self, spring: &Site) -> [(i64, i64); RIVER_SEGMENTS + 1] {
+        let mut course = [(0, 0); RIVER_SEGMENTS + 1];
+        for segment in 1..course.len() {
+            course[segment] = self.river_bend(spring, course[segment - 1], segment);
+        }
+        course
+    }
+
+    /// Where the river segment starting at `from` (an offset from the spring) ends
+    fn river_bend(&self, spring: &Site, (px, py): (i64, i64), segment: usize) -> (i64, i64) {
+        let (gx, gy) = self.slope(spring.x.wrapping_add(px), spring.y.wrapping_add(py));
+        let length = gx.hypot(gy);
+        let (dx, dy) = if length > 1e-9 {
+            (-gx / length, -gy / length)
+        } else {
+            // Flat ground: wander off in a direction of the spring's choosing
+            let angle = (mix(spring.hash, segment as i64, 0) >> 32) as f64 / u32::MAX as f64 * std::f64::consts::TAU;
+            (angle.cos(), angle.sin())
+        };
+        (px + (dx * RIVER_STEP).round() as i64, py + (dy * RIVER_STEP).round() as i64)
+    }
+
+    /// Whether the river of `spring` passes the tile at offset (dx, dy) from it.
+    /// Traced one segment at a time, giving up once the rest of the river is too short to reach.
+    fn on_river(&self, spring: &Site, dx: i64, dy: i64) -> bool {
+        let mut from = (0, 0);
+        for segment in 1..=RIVER_SEGMENTS {
+            let reach = (RIVER_SEGMENTS - segment + 1) as u64 * RIVER_STEP as u64;
+            if dx.wrapping_sub(from.0).unsigned_abs() > reach || dy.wrapping_sub(from.1).unsigned_abs() > reach {
+                return false;
+            }
+            let to = self.river_bend(spring, from, segment);
+            if line_glyph(from, to, (dx, dy)).is_some() {
+                return true;
+            }
+            from = to;
+        }
+        false
+    }
+
+    /// Terrain height at a tile, roughly in [-1, 1]
+    pub fn height(&self, x: i64, y: i64) -> f64 {
+        self.terrain.fractal(noise::tile_coord(x), noise::tile_coord(y), &TERRAIN)
+    }
+
+    /// Height gradient by forward differences
+    fn slope(&self, x: i64, y: i64) -> (f64, f64) {
+        let (x, y) = (noise::tile_coord(x), noise::tile_coord(y));
+        let height = |dx: f64, dy: f64| self.terrain.fractal(x + dx, y + dy, &TERRAIN);
+        let here = height(0.0, 0.0);
+        (height(1.0, 0.0) - here, height(0.0, 1.0) - here)
+    }
+
+    fn site_tile(&self, site: &Site, x: i64, y: i64) -> Option<Tile> {
+        let (dx, dy) = (x.wrapping_sub(site.x), y.wrapping_sub(site.y));
+        match site.kind {
+            SiteKind::Ruin => ruin_tile(site, dx, dy),
+            SiteKind::Monolith => monolith_tile(site, dx, dy),
+            SiteKind::Spring => (dx.unsigned_abs().saturating_add(dy.unsigned_abs()) <= 1).then(|| structure_tile('≈', PatternKind::River)),
+        }
+    }
+
+    /// Road glyph at (x, y), if a road between two neighbouring sites passes it.
+    /// A road spans the chunks between its ends, so both lie within one chunk of the tile.
+    fn road_glyph(&self, nearby: &Nearby, x: i64, y: i64) -> Option<char> {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let Some(start) = nearby.site(dx, dy) else {
+                    continue;
+                };
+                for (rx, ry) in ROAD_DIRECTIONS {
+                    let Some(end) = nearby.site(dx + rx, dy + ry) else {
+                        continue;
+                    };
+                    let to = (end.x.wrapping_sub(start.x), end.y.wrapping_sub(start.y));
+                    let glyph = line_glyph((0, 0), to, (x.wrapping_sub(start.x), y.wrapping_sub(start.y)));
+                    if glyph.is_some() && (mix(start.hash, rx, ry) >> 32) % 100 < ROAD_CHANCE {
+                        return glyph;
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Sites of the 3x3 chunks around a tile's chunk, hashed once per tile
+struct Nearby {
+    sites: [Option<Site>; 9],
+}
+
+impl Nearby {
+    fn new(layer: &StructureLayer, home: ChunkCoord) -> Self {
+        let mut sites = [None; 9];
+        for (index, site) in sites.iter_mut().enumerate() {
+            *site = layer.site_in(offset(home, index as i64 % 3 - 1, index as i64 / 3 - 1));
+        }
+        Nearby { sites }
+    }
+
+    /// Site of the chunk (dx, dy) chunks from home; `None` outside the 3x3
+    fn site(&self, dx: i64, dy: i64) -> Option<Site> {
+        if dx.abs() > 1 || dy.abs() > 1 {
+            return None;
+        }
+        self.sites[(dy * 3 + dx + 4) as usize]
+    }
+
+    fn sites(&self) -> impl Iterator<Item = Site> + '_ {
+        self.sites.iter().flatten().copied()
+    }
+}
+
+/// Walls of `+`, `-` and `|` around a blank floor, with crumbled gaps
+fn ruin_tile(site: &Site, dx: i64, dy: i64) -> Option<Tile> {
+    let shape = mix(site.hash, 0, 1) >> 32;
+    let (width, height) = (5 + (shape % 7) as i64, 3 + ((shape >> 8) % 4) as i64);
+    let (left, top) = (dx.wrapping_add(width / 2), dy.wrapping_add(height / 2));
+    if !(0..width).contains(&left) || !(0..height).contains(&top) {
+        return None;
+    }
+
+    let wear = (mix(site.hash, dx, dy) >> 32) % 16;
+    let side = left == 0 || left == width - 1;
+    let end = top == 0 || top == height - 1;
+    let glyph = match (side, end) {
+        _ if (side || end) && wear < 3 => return None,
+        _ if wear == 3 => ',',
+        (true, true) => '+',
+        (false, true) => '-',
+        (true, false) => '|',
+        (false, false) => ' ',
+    };
+    Some(structure_tile(glyph, PatternKind::Ruin))
+}
+
+/// A three-row slab around an inscription, centred on the site
+fn monolith_tile(site: &Site, dx: i64, dy: i64) -> Option<Tile> {
+    let text = INSCRIPTIONS[((mix(site.hash, 0, 1) >> 32) % INSCRIPTIONS.len() as u64) as usize];
+    let width = text.len() as i64 + 4;
+    let left = dx.wrapping_add(width / 2);
+    if !(0..width).contains(&left) || !(-1..=1).contains(&dy) {
+        return None;
+    }
+
+    let tile = match dy {
+        -1 => structure_tile('▄', PatternKind::Monolith),
+        1 => structure_tile('▀', PatternKind::Monolith),
+        _ if left == 0 || left == width - 1 => structure_tile('█', PatternKind::Monolith),
+        _ => {
+            let glyph = usize::try_from(left - 2).ok().and_then(|i| text.as_bytes().get(i)).map_or(' ', |&b| b as char);
+            Tile {
+                intensity: u8::MAX,
+                ..structure_tile(glyph, PatternKind::Monolith)
+            }
+        }
+    };
+    Some(tile)
+}
+
+fn structure_tile(glyph: char, pattern: PatternKind) -> Tile {
+    Tile::new(glyph, Biome::Structure).with_pattern(pattern)
+}
+
+/// Glyph of `p` if it lies on the rasterised line from `a` to `b`: `-` or `|` along
+/// the line, `/` or `\` where it steps sideways
+fn line_glyph(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> Option<char> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (px, py) = (p.0.wrapping_sub(a.0), p.1.wrapping_sub(a.1));
+    let x_major = dx.abs() >= dy.abs();
+    let (major, minor, along, across) = if x_major { (dx, dy, px, py) } else { (dy, dx, py, px) };
+    if major == 0 {
+        return (along == 0 && across == 0).then_some('-');
+    }
+    if along.signum() == -major.signum() || along.unsigned_abs() > major.unsigned_abs() {
+        return None;
+    }
+
+    let row = |t: i64| (t as f64 * minor as f64 / major as f64).round() as i64;
+    if row(along) != across {
+        return None;
+    }
+    let glyph = if along != 0 && row(along - major.signum()) != across {
+        if (dx > 0) == (dy > 0) { '\\' } else { '/' }
+    } else if x_major {
+        '-'
+    } else {
+        '|'
+    };
+    Some(glyph)
+}
+
+fn offset(chunk: ChunkCoord, dx: i64, dy: i64) -> ChunkCoord {
+    ChunkCoord {
+        x: chunk.x.wrapping_add(dx),
+        y: chunk.y.wrapping_add(dy),
+    }
+}
+
+/// Hash mixing as in `World::coord_hash`
+fn mix(mut h: u64, x: i64, y: i64) -> u64 {
+    h = h.wrapping_mul(HASH_MUL);
+    h ^= x as u64;
+    h = h.wrapping_mul(HASH_MUL);
+    h ^= y as u64;
+    h = h.wrapping_mul(HASH_MUL);
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Structure tiles in a square around the origin
+    fn survey(layer: &StructureLayer, size: i64) -> Vec<Tile> {
+        (-size..size).flat_map(|y| (-size..size).filter_map(move |x| layer.tile_at(x, y))).collect()
+    }
+
+    #[test]
+    fn test_sites_are_deterministic() {
+        let layer = StructureLayer::new(7);
+        let mut kinds = Vec::new();
+        for cy in -20..20 {
+            for cx in -20..20 {
+                let chunk = ChunkCoord { x: cx, y: cy };
+                let site = layer.site_in(chunk);
+                assert_eq!(site, StructureLayer::new(7).site_in(chunk));
+                if let Some(site) = site {
+                    assert_eq!(ChunkCoord::containing(site.x, site.y), chunk);
+                    kinds.push(site.kind);
+                }
+            }
+        }
+        // Roughly one chunk in three, every kind represented
+        assert!((400..700).contains(&kinds.len()), "{} sites", kinds.len());
+        for kind in SiteKind::ALL {
+            assert!(kinds.contains(&kind), "no {}", kind.name());
+        }
+        assert_ne!(survey(&layer, 64), survey(&StructureLayer::new(8), 64));
+    }
+
+    #[test]
+    fn test_structures_span_many_tiles() {
+        let layer = StructureLayer::new(7);
+        let tiles = survey(&layer, 256);
+        for pattern in [PatternKind::Ruin, PatternKind::Monolith, PatternKind::River, PatternKind::Road] {
+            let count = tiles.iter().filter(|tile| tile.pattern == pattern).count();
+            assert!(count > 20, "{} {:?} tiles", count, pattern);
+        }
+        assert!(tiles.iter().all(|tile| tile.biome == Biome::Structure));
+        assert!(tiles.iter().any(|tile| tile.character == '='), "no bridges");
+
+        // Monoliths read as text
+        let site = (0..)
+            .filter_map(|cx| layer.site_in(ChunkCoord { x: cx, y: 0 }))
+            .find(|site| site.kind == SiteKind::Monolith)
+            .unwrap();
+        let row: String = (-8..=8).filter_map(|dx| monolith_tile(&site, dx, 0)).map(|tile| tile.character).collect();
+        assert!(row.starts_with("█ ") && row.ends_with(" █"), "{:?}", row);
+        assert!(INSCRIPTIONS.contains(&row.trim_matches(|c| c == '█' || c == ' ')));
+    }
+
+    #[test]
+    fn test_rivers_run_downhill() {
+        let layer = StructureLayer::new(7);
+        let springs: Vec<Site> = (-15..15)
+            .flat_map(|cy| (-15..15).map(move |cx| ChunkCoord { x: cx, y: cy }))
+            .filter_map(|chunk| layer.site_in(chunk))
+            .filter(|site| site.kind == SiteKind::Spring)
+            .collect();
+        assert!(springs.len() > 20);
+
+        let mut downhill = 0;
+        for spring in &springs {
+            let course = layer.river_course(spring);
+            let height = |(dx, dy): (i64, i64)| layer.height(spring.x + dx, spring.y + dy);
+            if height(course[1]) < height(course[0]) {
+                downhill += 1;
+            }
+            // Every tile along the course is water
+            let (mx, my) = course[2];
+            let tile = layer.tile_at(spring.x + mx, spring.y + my);
+            assert!(tile.is_some(), "dry river at {:?}", (mx, my));
+        }
+        assert!(downhill * 10 >= springs.len() * 9, "{} of {} rivers run downhill", downhill, springs.len());
+    }
+
+    #[test]
+    fn test_line_glyph() {
+        let line: Vec<_> = (0..=4).map(|x| (0..3).find_map(|y| line_glyph((0, 0), (4, 2), (x, y)))).collect();
+        assert_eq!(line, [Some('-'), Some('\\'), Some('-'), Some('\\'), Some('-')]);
+        assert_eq!(line_glyph((0, 0), (0, -3), (0, -2)), Some('|'));
+        assert_eq!(line_glyph((0, 0), (3, -3), (2, -2)), Some('/'));
+        assert_eq!(line_glyph((0, 0), (4, 2), (5, 2)), None);
+        assert_eq!(line_glyph((0, 0), (4, 2), (-1, 0)), None);
+        assert_eq!(line_glyph((1, 1), (1, 1), (1, 1)), Some('-'));
+    }
+}
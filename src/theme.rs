@@ -0,0 +1,485 @@
+//! Colour themes for EndlessUtopia
+//! A `Theme` maps biomes, pattern kinds and UI roles to colours for every renderer
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::colour::Colour;
+use crate::world::{Biome, PatternKind, Tile};
+
+/// Name of the theme used when none is chosen
+pub const DEFAULT_THEME: &str = "classic";
+
+/// Parts of the interface that are not world tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Canvas and page background (terminals keep their own)
+    Background,
+    /// Logo, status line and the base text colour of exports
+    World,
+    /// The ascii cat and its glow
+    Cat,
+    /// Command output and prompt
+    Terminal,
+    Grid,
+    /// Freehand drawings
+    Drawing,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [Role::Background, Role::World, Role::Cat, Role::Terminal, Role::Grid, Role::Drawing];
+
+    /// Name used in theme files, e.g. `ui.background`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Background => "background",
+            Role::World => "world",
+            Role::Cat => "cat",
+            Role::Terminal => "terminal",
+            Role::Grid => "grid",
+            Role::Drawing => "drawing",
+        }
+    }
+}
+
+/// Names of the biome slots of a theme; every custom biome shares `custom`,
+/// every creature `creature` (the same names as the export CSS classes)
+const BIOME_NAMES: [&str; 8] = ["calm", "pattern", "glitch", "cat-trace", "cat", "custom", "creature", "structure"];
+
+fn biome_slot(biome: Biome) -> usize {
+    match biome {
+        Biome::Calm => 0,
+        Biome::Pattern => 1,
+        Biome::Glitch => 2,
+        Biome::CatTrace => 3,
+        Biome::CatPresent => 4,
+        Biome::Custom(_) => 5,
+        Biome::Creature(_) => 6,
+        Biome::Structure => 7,
+    }
+}
+
+fn pattern_slot(pattern: PatternKind) -> usize {
+    PatternKind::ALL.iter().position(|&p| p == pattern).unwrap_or(0)
+}
+
+/// Why a theme file could not be read
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeError {
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// Colours for tiles (by biome, optionally refined by pattern kind) and the interface.
+/// Tiles whose biome and pattern kind the theme leaves unset keep their own colour.
+///
+/// Themes are written as `key value` lines: `name`, `base` (a built-in theme to start
+/// from, usually first), `ui.<role>`, `biome.<biome>` and `pattern.<pattern>`, with
+/// colours as a palette index `0`-`15` or `#rrggbb` (`none` clears an override).
+/// `#` at the start of a line is a comment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    name: String,
+    ui: [Colour; Role::ALL.len()],
+    biomes: [Option<Colour>; BIOME_NAMES.len()],
+    /// Overrides the biome colour for tiles of a pattern kind
+    patterns: [Option<Colour>; PatternKind::ALL.len()],
+}
+
+impl Theme {
+    /// Green phosphor: the colours the generators give their tiles
+    pub fn classic() -> Self {
+        Theme {
+            name: "classic".to_string(),
+            ui: [
+                Colour::Rgb(0x11, 0x11, 0x11),
+                Colour::Rgb(0x00, 0xff, 0x00),
+                Colour::Rgb(0xff, 0xff, 0x00),
+                Colour::Rgb(0xff, 0xaa, 0x00),
+                Colour::Rgb(0x1a, 0x1a, 0x1a),
+                Colour::Rgb(0x00, 0xff, 0x00),
+            ],
+            biomes: [None; BIOME_NAMES.len()],
+            patterns: [None; PatternKind::ALL.len()],
+        }
+    }
+
+    /// Amber monochrome monitor
+    pub fn amber() -> Self {
+        Theme {
+            name: "amber".to_string(),
+            ui: [
+                Colour::Rgb(0x14, 0x0c, 0x00),
+                Colour::Rgb(0xff, 0xb0, 0x00),
+                Colour::Rgb(0xff, 0xe0, 0x8a),
+                Colour::Rgb(0xff, 0xb0, 0x00),
+                Colour::Rgb(0x2a, 0x1c, 0x00),
+                Colour::Rgb(0xff, 0xc3, 0x40),
+            ],
+            biomes: [
+                Colour::Rgb(0xb3, 0x7a, 0x00),
+                Colour::Rgb(0xff, 0xb0, 0x00),
+                Colour::Rgb(0xff, 0x7a, 0x00),
+                Colour::Rgb(0x7a, 0x52, 0x00),
+                Colour::Rgb(0xff, 0xe0, 0x8a),
+                Colour::Rgb(0xff, 0xc3, 0x40),
+                Colour::Rgb(0xff, 0xf0, 0xc0),
+                Colour::Rgb(0xd9, 0x96, 0x30),
+            ]
+            .map(Some),
+            patterns: [None; PatternKind::ALL.len()],
+        }
+        .with_pattern(PatternKind::GlitchIntense, Colour::Rgb(0xff, 0x50, 0x00))
+    }
+
+    /// Ethan Schoonover's Solarized (dark)
+    pub fn solarized() -> Self {
+        Theme {
+            name: "solarized".to_string(),
+            ui: [
+                Colour::Rgb(0x00, 0x2b, 0x36),
+                Colour::Rgb(0x83, 0x94, 0x96),
+                Colour::Rgb(0xb5, 0x89, 0x00),
+                Colour::Rgb(0xcb, 0x4b, 0x16),
+                Colour::Rgb(0x07, 0x36, 0x42),
+                Colour::Rgb(0x85, 0x99, 0x00),
+            ],
+            biomes: [
+                Colour::Rgb(0x85, 0x99, 0x00),
+                Colour::Rgb(0x2a, 0xa1, 0x98),
+                Colour::Rgb(0xd3, 0x36, 0x82),
+                Colour::Rgb(0x58, 0x6e, 0x75),
+                Colour::Rgb(0xb5, 0x89, 0x00),
+                Colour::Rgb(0x26, 0x8b, 0xd2),
+                Colour::Rgb(0xee, 0xe8, 0xd5),
+                Colour::Rgb(0x93, 0xa1, 0xa1),
+            ]
+            .map(Some),
+            patterns: [None; PatternKind::ALL.len()],
+        }
+        .with_pattern(PatternKind::Wave, Colour::Rgb(0x26, 0x8b, 0xd2))
+        .with_pattern(PatternKind::Rings, Colour::Rgb(0xb5, 0x89, 0x00))
+        .with_pattern(PatternKind::GlitchLight, Colour::Rgb(0x6c, 0x71, 0xc4))
+        .with_pattern(PatternKind::GlitchIntense, Colour::Rgb(0xdc, 0x32, 0x2f))
+        .with_pattern(PatternKind::River, Colour::Rgb(0x26, 0x8b, 0xd2))
+        .with_pattern(PatternKind::Road, Colour::Rgb(0xcb, 0x4b, 0x16))
+    }
+
+    /// Bright palette colours on black
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "high-contrast".to_string(),
+            ui: [
+                Colour::Rgb(0x00, 0x00, 0x00),
+                Colour::BRIGHT_WHITE,
+                Colour::BRIGHT_YELLOW,
+                Colour::BRIGHT_WHITE,
+                Colour::GREY,
+                Colour::BRIGHT_CYAN,
+            ],
+            biomes: [
+                Colour::BRIGHT_GREEN,
+                Colour::BRIGHT_CYAN,
+                Colour::BRIGHT_MAGENTA,
+                Colour::Palette(7),
+                Colour::BRIGHT_YELLOW,
+                Colour::Palette(12),
+                Colour::BRIGHT_WHITE,
+                Colour::BRIGHT_WHITE,
+            ]
+            .map(Some),
+            patterns: [None; PatternKind::ALL.len()],
+        }
+        .with_pattern(PatternKind::GlitchIntense, Colour::Palette(9))
+        .with_pattern(PatternKind::River, Colour::Palette(12))
+    }
+
+    /// Greys only; intensity still sets tiles apart
+    pub fn monochrome() -> Self {
+        Theme {
+            name: "monochrome".to_string(),
+            ui: [
+                Colour::Rgb(0x00, 0x00, 0x00),
+                Colour::Palette(7),
+                Colour::BRIGHT_WHITE,
+                Colour::Palette(7),
+                Colour::Rgb(0x22, 0x22, 0x22),
+                Colour::BRIGHT_WHITE,
+            ],
+            biomes: [
+                Colour::Palette(7),
+                Colour::Palette(7),
+                Colour::BRIGHT_WHITE,
+                Colour::GREY,
+                Colour::BRIGHT_WHITE,
+                Colour::Palette(7),
+                Colour::BRIGHT_WHITE,
+                Colour::Palette(7),
+            ]
+            .map(Some),
+            patterns: [None; PatternKind::ALL.len()],
+        }
+    }
+
+    /// Every built-in theme, `classic` first
+    pub fn builtin() -> Vec<Theme> {
+        vec![Theme::classic(), Theme::amber(), Theme::solarized(), Theme::high_contrast(), Theme::monochrome()]
+    }
+
+    /// A built-in theme by name
+    pub fn named(name: &str) -> Option<Theme> {
+        Theme::builtin().into_iter().find(|theme| theme.name == name)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Colour of an interface role
+    pub fn ui(&self, role: Role) -> Colour {
+        self.ui[role as usize]
+    }
+
+    /// Colour of a biome's tiles (without pattern overrides)
+    /// Colour of a biome, or its own colour if the theme leaves it unset
+    pub fn biome(&self, biome: Biome) -> Colour {
+        self.biomes[biome_slot(biome)].unwrap_or_else(|| biome.colour())
+    }
+
+    /// Colour override for a pattern kind, if the theme has one
+    pub fn pattern(&self, pattern: PatternKind) -> Option<Colour> {
+        self.patterns[pattern_slot(pattern)]
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_ui(mut self, role: Role, colour: Colour) -> Self {
+        self.ui[role as usize] = colour;
+        self
+    }
+
+    pub fn with_biome(mut self, biome: Biome, colour: Colour) -> Self {
+        self.biomes[biome_slot(biome)] = Some(colour);
+        self
+    }
+
+    pub fn with_pattern(mut self, pattern: PatternKind, colour: Colour) -> Self {
+        self.patterns[pattern_slot(pattern)] = Some(colour);
+        self
+    }
+
+    /// Foreground colour of a tile under this theme, falling back to the tile's own
+    pub fn tile_colour(&self, tile: &Tile) -> Colour {
+        self.pattern(tile.pattern)
+            .or(self.biomes[biome_slot(tile.biome)])
+            .unwrap_or(tile.fg)
+    }
+
+    /// The tile recoloured by this theme (background and intensity are kept)
+    pub fn apply(&self, tile: Tile) -> Tile {
+        Tile {
+            fg: self.tile_colour(&tile),
+            ..tile
+        }
+    }
+
+    /// Read a theme file; keys it leaves out keep their `classic` colours
+    pub fn parse(text: &str) -> Result<Theme, ThemeError> {
+        let mut theme = Theme::classic().with_name("custom");
+        for (index, line) in text.lines().enumerate() {
+            theme
+                .read_line(line.trim())
+                .map_err(|message| ThemeError { line: index + 1, message })?;
+        }
+        Ok(theme)
+    }
+
+    /// Apply one `key value` line
+    fn read_line(&mut self, line: &str) -> Result<(), String> {
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim();
+        match key.split_once('.') {
+            None if key == "name" && !value.is_empty() => self.name = value.to_string(),
+            None if key == "base" => {
+                let base = Theme::named(value).ok_or_else(|| format!("unknown base theme '{}'", value))?;
+                *self = base.with_name(std::mem::take(&mut self.name));
+            }
+            Some(("ui", role)) => {
+                let role = Role::ALL.iter().position(|r| r.name() == role).ok_or_else(|| format!("unknown role '{}'", role))?;
+                self.ui[role] = value.parse()?;
+            }
+            Some(("biome", biome)) => {
+                let slot = BIOME_NAMES.iter().position(|&b| b == biome).ok_or_else(|| format!("unknown biome '{}'", biome))?;
+                self.biomes[slot] = match value {
+                    "none" => None,
+                    colour => Some(colour.parse()?),
+                };
+            }
+            Some(("pattern", pattern)) => {
+                let slot = PatternKind::ALL
+                    .iter()
+                    .position(|p| p.name() == pattern)
+                    .ok_or_else(|| format!("unknown pattern '{}'", pattern))?;
+                self.patterns[slot] = match value {
+                    "none" => None,
+                    colour => Some(colour.parse()?),
+                };
+            }
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::parse(s)
+    }
+}
+
+/// Palette colours as their index, so they stay palette colours when read back
+fn theme_colour(colour: Colour) -> String {
+    match colour {
+        Colour::Palette(index) => index.to_string(),
+        Colour::Rgb(..) => colour.to_string(),
+    }
+}
+
+/// Writes the theme file format read by `Theme::parse`
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name {}", self.name)?;
+        for role in Role::ALL {
+            writeln!(f, "ui.{} {}", role.name(), theme_colour(self.ui(role)))?;
+        }
+        for (name, colour) in BIOME_NAMES.iter().zip(self.biomes) {
+            let colour = colour.map_or("none".to_string(), theme_colour);
+            writeln!(f, "biome.{} {}", name, colour)?;
+        }
+        for (pattern, colour) in PatternKind::ALL.iter().zip(self.patterns) {
+            let colour = colour.map_or("none".to_string(), theme_colour);
+            writeln!(f, "pattern.{} {}", pattern.name(), colour)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biome::BiomeGenerator;
+    use crate::region::Region;
+    use crate::world::World;
+
+    #[test]
+    fn test_classic_keeps_tile_colours() {
+        let world = World::new();
+        let mut region = Region::new(-200, 300, 120, 60);
+        world.fill_region(&mut region);
+        let theme = Theme::classic();
+        assert!(region.rows().flatten().all(|&tile| theme.apply(tile) == tile));
+        assert_eq!(theme.ui(Role::Background).to_string(), "#111111");
+        assert_eq!(Theme::named(DEFAULT_THEME), Some(Theme::default()));
+    }
+
+    #[test]
+    fn test_builtin_themes() {
+        let themes = Theme::builtin();
+        let names: Vec<&str> = themes.iter().map(Theme::name).collect();
+        assert_eq!(names, ["classic", "amber", "solarized", "high-contrast", "monochrome"]);
+        for theme in &themes {
+            assert_eq!(theme.to_string().parse::<Theme>().as_ref(), Ok(theme));
+        }
+
+        let wave = Tile::new('~', Biome::Pattern).with_pattern(PatternKind::Wave);
+        assert_eq!(Theme::solarized().apply(wave).fg.to_string(), "#268bd2");
+        assert_eq!(Theme::amber().apply(wave).fg, Theme::amber().biome(Biome::Pattern));
+        assert_eq!(Theme::monochrome().tile_colour(&Tile::new('x', Biome::Creature("bird"))), Colour::BRIGHT_WHITE);
+        assert_eq!(Theme::named("neon"), None);
+    }
+
+    #[test]
+    fn test_unset_colours_keep_the_tile_colour() {
+        const LAVA: Colour = Colour::Rgb(0xff, 0x45, 0x00);
+        struct Lava;
+
+        impl BiomeGenerator for Lava {
+            fn name(&self) -> &str {
+                "lava"
+            }
+
+            fn biome(&self) -> Biome {
+                Biome::Custom("lava")
+            }
+
+            fn weight(&self) -> u32 {
+                1
+            }
+
+            fn char_at(&self, _x: i64, _y: i64, _hash: u64) -> char {
+                '~'
+            }
+
+            fn tile_at(&self, x: i64, y: i64, hash: u64) -> Tile {
+                Tile {
+                    fg: LAVA,
+                    ..Tile::new(self.char_at(x, y, hash), self.biome()).with_pattern(PatternKind::Wave)
+                }
+            }
+        }
+
+        let world = World::builder().without_default_biomes().biome(Lava).build();
+        let tile = world.get_tile(3, 4);
+        assert_eq!(Theme::classic().apply(tile).fg, LAVA);
+        assert_eq!(Theme::parse("biome.calm 4").unwrap().apply(tile).fg, LAVA);
+        assert_eq!(Theme::parse("biome.custom 4").unwrap().apply(tile).fg, Colour::Palette(4));
+        assert_eq!(Theme::parse("pattern.wave 5").unwrap().apply(tile).fg, Colour::Palette(5));
+        assert_eq!(Theme::amber().apply(tile).fg, Theme::amber().biome(Biome::Custom("lava")));
+        assert_eq!(Theme::parse("base amber\nbiome.custom none").unwrap().apply(tile).fg, LAVA);
+    }
+
+    #[test]
+    fn test_parse_custom_theme() {
+        let text = "# dusk\nname dusk\nbase amber\n\nui.grid #333\nbiome.custom 12\npattern.glitch-heavy #ff0000\n";
+        let theme = Theme::parse(text).unwrap();
+        assert_eq!(theme.name(), "dusk");
+        assert_eq!(theme.ui(Role::Grid), Colour::Rgb(0x33, 0x33, 0x33));
+        assert_eq!(theme.ui(Role::Cat), Theme::amber().ui(Role::Cat));
+        assert_eq!(theme.biome(Biome::Custom("forest")), Colour::Palette(12));
+        assert_eq!(theme.pattern(PatternKind::GlitchHeavy), Some(Colour::Rgb(255, 0, 0)));
+        assert_eq!(Theme::parse("pattern.wave none").unwrap().pattern(PatternKind::Wave), None);
+
+        // Without a base, unset keys are classic
+        assert_eq!(Theme::parse("ui.cat 3").unwrap().biome(Biome::Calm), Colour::GREEN);
+
+        let error = Theme::parse("name x\nbiome.lava #f00\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(error.message.contains("'lava'"));
+        assert!(Theme::parse("ui.grid green").is_err());
+        assert!(Theme::parse("base neon").is_err());
+        assert!(Theme::parse("colour red").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Cat trails for EndlessUtopia
+//! The path the cat walked, as time-ordered steps that fade and eventually expire
+
+use std::collections::VecDeque;
+
+use crate::region::Rect;
+
+/// Steps older than this many ticks are forgotten
+pub const TRAIL_TICKS: u64 = 256;
+
+/// The cat was on tile (x, y), last seen there at `tick`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrailStep {
+    pub x: i64,
+    pub y: i64,
+    pub tick: u64,
+}
+
+impl TrailStep {
+    /// Ticks since the step, as seen at `now`
+    pub fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.tick)
+    }
+}
+
+/// Time-ordered steps, oldest first. Standing still updates the last step
+/// instead of adding a new one, so each step is one tile of the path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trail {
+    steps: VecDeque<TrailStep>,
+}
+
+impl Trail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the cat at (x, y) at `tick`. Ticks must not go backwards;
+    /// call `rewind` first when time is reset.
+    pub fn record(&mut self, x: i64, y: i64, tick: u64) {
+        match self.steps.back_mut() {
+            Some(last) if (last.x, last.y) == (x, y) => last.tick = last.tick.max(tick),
+            _ => self.steps.push_back(TrailStep { x, y, tick }),
+        }
+        self.expire(tick);
+    }
+
+    /// Drop steps that have aged past `TRAIL_TICKS` at `now`
+    pub fn expire(&mut self, now: u64) {
+        while self.steps.front().is_some_and(|step| step.age(now) > TRAIL_TICKS) {
+            self.steps.pop_front();
+        }
+    }
+
+    /// Forget everything after `tick` (time went backwards)
+    pub fn rewind(&mut self, tick: u64) {
+        while self.steps.back().is_some_and(|step| step.tick > tick) {
+            self.steps.pop_back();
+        }
+    }
+
+    /// Most recent step, if any
+    pub fn last(&self) -> Option<&TrailStep> {
+        self.steps.back()
+    }
+
+    /// All steps, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &TrailStep> {
+        self.steps.iter()
+    }
+
+    /// Steps inside `rect`, oldest first
+    pub fn in_rect(&self, rect: Rect) -> impl Iterator<Item = &TrailStep> {
+        self.steps.iter().filter(move |step| rect.contains(step.x, step.y))
+    }
+
+    /// Latest step on tile (x, y)
+    pub fn latest_at(&self, x: i64, y: i64) -> Option<&TrailStep> {
+        self.steps.iter().rev().find(|step| (step.x, step.y) == (x, y))
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_merges_standing_still() {
+        let mut trail = Trail::new();
+        trail.record(0, 0, 1);
+        trail.record(0, 0, 2);
+        trail.record(1, 0, 3);
+        trail.record(1, 0, 5);
+
+        let steps: Vec<_> = trail.iter().copied().collect();
+        assert_eq!(steps, vec![TrailStep { x: 0, y: 0, tick: 2 }, TrailStep { x: 1, y: 0, tick: 5 }]);
+        assert_eq!(trail.last().unwrap().age(9), 4);
+    }
+
+    #[test]
+    fn test_expire_and_rewind() {
+        let mut trail = Trail::new();
+        for tick in 0..10 {
+            trail.record(tick as i64, 0, tick);
+        }
+
+        trail.record(10, 0, 10 + TRAIL_TICKS - 3);
+        assert_eq!(trail.iter().next().unwrap().tick, 7);
+
+        trail.rewind(8);
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail.last().unwrap().x, 8);
+    }
+
+    #[test]
+    fn test_rect_query() {
+        let mut trail = Trail::new();
+        for (tick, x) in (0..20).enumerate() {
+            trail.record(x, x, tick as u64);
+        }
+        trail.record(5, 5, 30);
+
+        let inside: Vec<_> = trail.in_rect(Rect::new(4, 4, 3, 3)).map(|s| (s.x, s.tick)).collect();
+        assert_eq!(inside, vec![(4, 4), (5, 5), (6, 6), (5, 30)]);
+        assert_eq!(trail.latest_at(5, 5).unwrap().tick, 30);
+        assert!(trail.latest_at(50, 50).is_none());
+    }
+}
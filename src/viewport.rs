@@ -0,0 +1,312 @@
+//! Camera for EndlessUtopia frontends
+//! A `Viewport` maps between world tile coordinates and screen pixels
+
+use crate::region::Rect;
+
+/// Size of one tile on screen at zoom 1 (a 12px monospace glyph cell, as in the
+/// browser and the SVG exporter)
+pub const DEFAULT_TILE_WIDTH: f64 = 7.2;
+pub const DEFAULT_TILE_HEIGHT: f64 = 16.0;
+
+/// Zoom limits enforced by `Viewport::zoom_at`
+pub const MIN_ZOOM: f64 = 1.0 / 32.0;
+pub const MAX_ZOOM: f64 = 4.0;
+
+/// A point in the world, kept like the screen centre: a whole tile plus an offset
+/// into it, each in [0, 1), so it is exact anywhere in the i64 world
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WorldPoint {
+    pub tile_x: i64,
+    pub tile_y: i64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl WorldPoint {
+    /// The point at world (tile) coordinates (x, y)
+    pub fn from_f64(x: f64, y: f64) -> Self {
+        let (tile_x, offset_x) = split(to_tile(x), x - x.floor());
+        let (tile_y, offset_y) = split(to_tile(y), y - y.floor());
+        WorldPoint { tile_x, tile_y, offset_x, offset_y }
+    }
+
+    /// The point in world (tile) coordinates, rounded to f64 far from the origin
+    pub fn to_f64(self) -> (f64, f64) {
+        (self.tile_x as f64 + self.offset_x, self.tile_y as f64 + self.offset_y)
+    }
+
+    /// The same point with both offsets moved into [0, 1)
+    pub fn normalised(self) -> Self {
+        let (tile_x, offset_x) = split(self.tile_x, self.offset_x);
+        let (tile_y, offset_y) = split(self.tile_y, self.offset_y);
+        WorldPoint { tile_x, tile_y, offset_x, offset_y }
+    }
+}
+
+/// What part of the world is on screen. World coordinates are tiles: tile (x, y)
+/// covers [x, x + 1) x [y, y + 1). The screen centre is kept as a whole tile plus
+/// an offset into it, so panning and zooming stay exact anywhere in the i64 world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Tile under the screen centre
+    pub tile_x: i64,
+    pub tile_y: i64,
+    /// Where in that tile the centre lies, each in [0, 1)
+    pub offset_x: f64,
+    pub offset_y: f64,
+    /// Screen scale; 1 draws tiles at `tile_width` x `tile_height` pixels
+    pub zoom: f64,
+    pub tile_width: f64,
+    pub tile_height: f64,
+    /// Screen size in pixels
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Viewport {
+    /// A `width` x `height` pixel screen centred on the origin at zoom 1
+    pub fn new(tile_width: f64, tile_height: f64, width: f64, height: f64) -> Self {
+        Viewport {
+            tile_x: 0,
+            tile_y: 0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+            tile_width,
+            tile_height,
+            width,
+            height,
+        }
+    }
+
+    pub fn set_size(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Size of one tile on screen at the current zoom
+    pub fn tile_size(&self) -> (f64, f64) {
+        (self.tile_width * self.zoom, self.tile_height * self.zoom)
+    }
+
+    /// World point at the screen centre (rounded to f64 far from the origin)
+    pub fn centre(&self) -> (f64, f64) {
+        (self.tile_x as f64 + self.offset_x, self.tile_y as f64 + self.offset_y)
+    }
+
+    /// Centre the screen on world point (x, y)
+    pub fn set_centre(&mut self, x: f64, y: f64) {
+        self.set_centre_point(WorldPoint::from_f64(x, y));
+    }
+
+    /// The world point at the screen centre, exact anywhere
+    pub fn centre_point(&self) -> WorldPoint {
+        WorldPoint {
+            tile_x: self.tile_x,
+            tile_y: self.tile_y,
+            offset_x: self.offset_x,
+            offset_y: self.offset_y,
+        }
+    }
+
+    /// Centre the screen on `point`
+    pub fn set_centre_point(&mut self, point: WorldPoint) {
+        let point = point.normalised();
+        (self.tile_x, self.tile_y) = (point.tile_x, point.tile_y);
+        (self.offset_x, self.offset_y) = (point.offset_x, point.offset_y);
+    }
+
+    /// Centre the screen on the top-left corner of tile (x, y)
+    pub fn centre_on(&mut self, x: i64, y: i64) {
+        (self.tile_x, self.tile_y) = (x, y);
+        (self.offset_x, self.offset_y) = (0.0, 0.0);
+    }
+
+    /// Screen pixel (x, y) in tiles from the tile under the centre
+    fn relative(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
+        let (tile_width, tile_height) = self.tile_size();
+        (
+            self.offset_x + (screen_x - self.width / 2.0) / tile_width,
+            self.offset_y + (screen_y - self.height / 2.0) / tile_height,
+        )
+    }
+
+    /// World (tile) coordinates under screen pixel (x, y), rounded to f64
+    pub fn screen_to_world(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
+        let (x, y) = self.relative(screen_x, screen_y);
+        (self.tile_x as f64 + x, self.tile_y as f64 + y)
+    }
+
+    /// Screen pixel of world (tile) coordinates (x, y). Exact near the origin;
+    /// use `tile_to_screen` for tiles far out.
+    pub fn world_to_screen(&self, world_x: f64, world_y: f64) -> (f64, f64) {
+        let (tile_width, tile_height) = self.tile_size();
+        (
+            (world_x - self.tile_x as f64 - self.offset_x) * tile_width + self.width / 2.0,
+            (world_y - self.tile_y as f64 - self.offset_y) * tile_height + self.height / 2.0,
+        )
+    }
+
+    /// World point under screen pixel (x, y), exact anywhere
+    pub fn point_at(&self, screen_x: f64, screen_y: f64) -> WorldPoint {
+        let (x, y) = self.relative(screen_x, screen_y);
+        WorldPoint {
+            tile_x: self.tile_x,
+            tile_y: self.tile_y,
+            offset_x: x,
+            offset_y: y,
+        }
+        .normalised()
+    }
+
+    /// Screen pixel of a world point, exact anywhere
+    pub fn point_to_screen(&self, point: WorldPoint) -> (f64, f64) {
+        let (x, y) = self.tile_to_screen(point.tile_x, point.tile_y);
+        let (tile_width, tile_height) = self.tile_size();
+        (x + point.offset_x * tile_width, y + point.offset_y * tile_height)
+    }
+
+    /// Screen pixel of the top-left corner of tile (x, y), exact anywhere
+    pub fn tile_to_screen(&self, x: i64, y: i64) -> (f64, f64) {
+        let (tile_width, tile_height) = self.tile_size();
+        let from_centre = |tile: i64, centre: i64, offset: f64| (tile as i128 - centre as i128) as f64 - offset;
+        (
+            from_centre(x, self.tile_x, self.offset_x) * tile_width + self.width / 2.0,
+            from_centre(y, self.tile_y, self.offset_y) * tile_height + self.height / 2.0,
+        )
+    }
+
+    /// The tile under screen pixel (x, y)
+    pub fn tile_at(&self, screen_x: f64, screen_y: f64) -> (i64, i64) {
+        let (x, y) = self.relative(screen_x, screen_y);
+        (self.tile_x.saturating_add(to_tile(x)), self.tile_y.saturating_add(to_tile(y)))
+    }
+
+    /// The tile at the centre of the screen
+    pub fn centre_tile(&self) -> (i64, i64) {
+        (self.tile_x, self.tile_y)
+    }
+
+    /// Every tile that is at least partly on screen
+    pub fn visible_rect(&self) -> Rect {
+        let (left, top) = self.relative(0.0, 0.0);
+        let (right, bottom) = self.relative(self.width, self.height);
+        let span = |from: f64, to: f64| (to.ceil() - from.floor()).max(0.0) as usize;
+        let (x, y) = self.tile_at(0.0, 0.0);
+        Rect::new(x, y, span(left, right), span(top, bottom))
+    }
+
+    /// Move the view by screen pixels (dragging right moves the world right)
+    pub fn drag(&mut self, dx: f64, dy: f64) {
+        let (tile_width, tile_height) = self.tile_size();
+        (self.tile_x, self.offset_x) = split(self.tile_x, self.offset_x - dx / tile_width);
+        (self.tile_y, self.offset_y) = split(self.tile_y, self.offset_y - dy / tile_height);
+    }
+
+    /// Multiply the zoom by `factor` (within MIN_ZOOM..=MAX_ZOOM), keeping the
+    /// world point under screen pixel (x, y) in place
+    pub fn zoom_at(&mut self, factor: f64, screen_x: f64, screen_y: f64) {
+        let before = self.relative(screen_x, screen_y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = self.relative(screen_x, screen_y);
+        (self.tile_x, self.offset_x) = split(self.tile_x, self.offset_x + before.0 - after.0);
+        (self.tile_y, self.offset_y) = split(self.tile_y, self.offset_y + before.1 - after.1);
+    }
+}
+
+/// Tile containing a coordinate, clamped to the i64 world
+fn to_tile(coord: f64) -> i64 {
+    coord.floor().clamp(i64::MIN as f64, i64::MAX as f64) as i64
+}
+
+/// Move whole tiles out of `offset` into `tile`, stopping at the edges of the world
+fn split(tile: i64, offset: f64) -> (i64, f64) {
+    let whole = offset.floor();
+    let tile = tile.saturating_add(to_tile(whole));
+    let offset = if offset.is_finite() { offset - whole } else { 0.0 };
+    (tile, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut view = Viewport::new(DEFAULT_TILE_WIDTH, DEFAULT_TILE_HEIGHT, 800.0, 600.0);
+        view.centre_on(100, -50);
+        assert_eq!(view.world_to_screen(100.0, -50.0), (400.0, 300.0));
+        assert_eq!(view.tile_to_screen(100, -50), (400.0, 300.0));
+        assert_eq!(view.centre_tile(), (100, -50));
+        assert_eq!(view.tile_at(401.0 + DEFAULT_TILE_WIDTH, 299.0), (101, -51));
+
+        view.zoom = 2.5;
+        view.set_centre(100.25, -49.5);
+        assert_eq!(view.centre(), (100.25, -49.5));
+        for point in [(0.0, 0.0), (123.4, 567.8), (-3.0, 1e6)] {
+            let (screen_x, screen_y) = view.world_to_screen(point.0, point.1);
+            assert!(close(view.screen_to_world(screen_x, screen_y), point));
+        }
+    }
+
+    #[test]
+    fn test_zoom_keeps_anchor() {
+        let mut view = Viewport::new(DEFAULT_TILE_WIDTH, DEFAULT_TILE_HEIGHT, 800.0, 600.0);
+        let anchor = view.screen_to_world(650.0, 80.0);
+        view.zoom_at(3.0, 650.0, 80.0);
+        assert_eq!(view.zoom, 3.0);
+        assert!(close(view.screen_to_world(650.0, 80.0), anchor));
+
+        view.zoom_at(1e-9, 0.0, 0.0);
+        assert_eq!(view.zoom, MIN_ZOOM);
+
+        // Dragging by one tile moves the centre by one tile
+        let (tile_width, _) = view.tile_size();
+        let (x, _) = view.centre();
+        view.drag(tile_width, 0.0);
+        assert!((view.centre().0 - (x - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_visible_rect() {
+        let mut view = Viewport::new(1.0, 1.0, 80.0, 24.0);
+        assert_eq!(view.visible_rect(), Rect::new(-40, -12, 80, 24));
+
+        view.offset_x = 0.5;
+        assert_eq!(view.visible_rect(), Rect::new(-40, -12, 81, 24));
+
+        view.set_centre(f64::MAX, 0.0);
+        assert_eq!(view.centre_tile().0, i64::MAX);
+    }
+
+    #[test]
+    fn test_exact_far_from_origin() {
+        let mut view = Viewport::new(1.0, 1.0, 80.0, 24.0);
+        view.centre_on(i64::MAX - 1000, i64::MIN + 20);
+        assert_eq!(view.visible_rect(), Rect::new(i64::MAX - 1040, i64::MIN + 8, 80, 24));
+
+        // A quarter of a tile at a time still adds up where f64 alone could not
+        for _ in 0..4 {
+            view.drag(-0.25, 0.0);
+        }
+        assert_eq!(view.centre_tile(), (i64::MAX - 999, i64::MIN + 20));
+        assert_eq!(view.tile_to_screen(i64::MAX - 999, i64::MIN + 20), (40.0, 12.0));
+        assert_eq!(view.tile_at(79.0, 0.0), (i64::MAX - 960, i64::MIN + 8));
+
+        // Points under the cursor are exact too
+        let point = view.point_at(50.5, 3.25);
+        assert_eq!((point.tile_x, point.tile_y), (i64::MAX - 989, i64::MIN + 11));
+        assert_eq!((point.offset_x, point.offset_y), (0.5, 0.25));
+        assert_eq!(view.point_to_screen(point), (50.5, 3.25));
+        view.set_centre_point(point);
+        assert_eq!(view.centre_point(), point);
+
+        // The camera stops at the edge of the world
+        view.drag(-1e6, 0.0);
+        assert_eq!(view.centre_tile().0, i64::MAX);
+    }
+}
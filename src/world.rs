@@ -1,13 +1,68 @@
-/// Core world generation module for EndlessUtopia
-/// Generates deterministic ASCII patterns based on coordinates
+//! Core world generation module for EndlessUtopia
+//! Generates deterministic ASCII patterns based on coordinates
 
-use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::biome::{BiomeGenerator, BiomeMode, BiomeRegistry};
+use crate::colour::Colour;
+use crate::chunk::{CacheStats, Chunk, ChunkCache, ChunkCoord, CHUNK_SIZE, DEFAULT_CACHE_BUDGET};
+use crate::entity::{Ascicat, Creature, Entity, EntityRegistry, Spawn};
+use crate::glyph::GlyphSet;
+use crate::landmark::Landmark;
+use crate::structure::StructureLayer;
+use crate::region::{Rect, Region};
+use crate::trail::TRAIL_TICKS;
+use crate::noise::{self, Noise};
 
 /// Represents a tile in the ASCII world
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Tile {
     pub character: char,
     pub biome: Biome,
+    /// Which sub-pattern of the biome produced `character`
+    pub pattern: PatternKind,
+    pub fg: Colour,
+    /// `None` leaves the renderer's background showing
+    pub bg: Option<Colour>,
+    /// Brightness from 0 (invisible) to 255; see `NORMAL_INTENSITY`
+    pub intensity: u8,
+}
+
+/// Intensity of ordinary tiles; cats and creatures stand out at 255
+pub const NORMAL_INTENSITY: u8 = 200;
+
+impl Tile {
+    /// A plain tile in the biome's default colour
+    pub fn new(character: char, biome: Biome) -> Self {
+        Tile {
+            character,
+            biome,
+            pattern: PatternKind::Plain,
+            fg: biome.colour(),
+            bg: None,
+            intensity: match biome {
+                Biome::CatPresent | Biome::Creature(_) => u8::MAX,
+                _ => NORMAL_INTENSITY,
+            },
+        }
+    }
+
+    /// The same tile, produced by `pattern` and styled like it
+    pub fn with_pattern(self, pattern: PatternKind) -> Self {
+        Tile {
+            pattern,
+            fg: pattern.colour().unwrap_or(self.fg),
+            intensity: pattern.intensity().unwrap_or(self.intensity),
+            ..self
+        }
+    }
+}
+
+/// A blank calm tile
+impl Default for Tile {
+    fn default() -> Self {
+        Tile::new(' ', Biome::Calm)
+    }
 }
 
 /// Different biomes/pattern types in the world
@@ -18,224 +73,838 @@ pub enum Biome {
     Glitch,      // Glitchy/corrupted areas
     CatTrace,    // Where the cat has been
     CatPresent,  // Current cat location
+    Custom(&'static str), // Biome registered by a custom `BiomeGenerator`
+    Creature(&'static str), // Tile occupied by a non-cat `Entity`
+    Structure,   // Ruins, monoliths, rivers and roads spanning many tiles
 }
 
-/// The infinite world generator
+impl Biome {
+    /// Default foreground colour of the biome's tiles
+    pub fn colour(&self) -> Colour {
+        match self {
+            Biome::Calm => Colour::GREEN,
+            Biome::Pattern => Colour::BRIGHT_GREEN,
+            Biome::Glitch => Colour::BRIGHT_MAGENTA,
+            Biome::CatTrace => Colour::GREY,
+            Biome::CatPresent => Colour::BRIGHT_YELLOW,
+            Biome::Custom(_) => Colour::BRIGHT_CYAN,
+            Biome::Creature(_) => Colour::BRIGHT_WHITE,
+            Biome::Structure => Colour::Palette(7),
+        }
+    }
+}
+
+/// The branch of a built-in biome that produced a tile, so renderers can tell
+/// sub-patterns apart without re-deriving the hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PatternKind {
+    /// Not part of a pattern: calm and custom biomes, traces and creatures
+    #[default]
+    Plain,
+    Checkerboard,
+    Wave,
+    Diagonal,
+    Dots,
+    Cross,
+    Rings,
+    Scatter,
+    Sparse,
+    GlitchLight,
+    GlitchMedium,
+    GlitchHeavy,
+    GlitchIntense,
+    /// The kinds of `Biome::Structure` tiles
+    Ruin,
+    Monolith,
+    River,
+    Road,
+}
+
+impl PatternKind {
+    pub const ALL: [PatternKind; 17] = [
+        PatternKind::Plain,
+        PatternKind::Checkerboard,
+        PatternKind::Wave,
+        PatternKind::Diagonal,
+        PatternKind::Dots,
+        PatternKind::Cross,
+        PatternKind::Rings,
+        PatternKind::Scatter,
+        PatternKind::Sparse,
+        PatternKind::GlitchLight,
+        PatternKind::GlitchMedium,
+        PatternKind::GlitchHeavy,
+        PatternKind::GlitchIntense,
+        PatternKind::Ruin,
+        PatternKind::Monolith,
+        PatternKind::River,
+        PatternKind::Road,
+    ];
+
+    /// Colour of the sub-pattern, if it differs from its biome's
+    pub fn colour(&self) -> Option<Colour> {
+        match self {
+            PatternKind::Checkerboard | PatternKind::Cross | PatternKind::Scatter => Some(Colour::GREEN),
+            PatternKind::Wave => Some(Colour::Palette(6)),
+            PatternKind::Rings => Some(Colour::Palette(3)),
+            PatternKind::GlitchLight => Some(Colour::Palette(5)),
+            PatternKind::GlitchIntense => Some(Colour::Palette(9)),
+            PatternKind::Monolith => Some(Colour::BRIGHT_WHITE),
+            PatternKind::River => Some(Colour::Palette(12)),
+            PatternKind::Road => Some(Colour::Palette(3)),
+            _ => None,
+        }
+    }
+
+    /// Intensity of the sub-pattern, if it differs from `NORMAL_INTENSITY`
+    pub fn intensity(&self) -> Option<u8> {
+        match self {
+            PatternKind::Sparse => Some(140),
+            PatternKind::GlitchLight => Some(160),
+            PatternKind::GlitchHeavy => Some(230),
+            PatternKind::GlitchIntense => Some(u8::MAX),
+            _ => None,
+        }
+    }
+
+    /// Lower-case name, e.g. "glitch-heavy"
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatternKind::Plain => "plain",
+            PatternKind::Checkerboard => "checkerboard",
+            PatternKind::Wave => "wave",
+            PatternKind::Diagonal => "diagonal",
+            PatternKind::Dots => "dots",
+            PatternKind::Cross => "cross",
+            PatternKind::Rings => "rings",
+            PatternKind::Scatter => "scatter",
+            PatternKind::Sparse => "sparse",
+            PatternKind::GlitchLight => "glitch-light",
+            PatternKind::GlitchMedium => "glitch-medium",
+            PatternKind::GlitchHeavy => "glitch-heavy",
+            PatternKind::GlitchIntense => "glitch-intense",
+            PatternKind::Ruin => "ruin",
+            PatternKind::Monolith => "monolith",
+            PatternKind::River => "river",
+            PatternKind::Road => "road",
+        }
+    }
+}
+
+/// Seed used by `World::new()` - reproduces the original, unseeded world
+pub const DEFAULT_SEED: u64 = 0x517cc1b727220a95;
+
+/// Regions with at least this many tiles are rendered in parallel (and bypass the cache)
+#[cfg(feature = "parallel")]
+pub const PARALLEL_MIN_TILES: usize = 256 * 256;
+
+/// Multiplier used by all hash mixing steps
+pub(crate) const HASH_MUL: u64 = 0x6c62272e07bb0142;
+
+/// The cat's home lies within -CAT_HOME_RANGE..CAT_HOME_RANGE on both axes: the i32 range of the original world
+pub const CAT_HOME_RANGE: i64 = 1 << 31;
+
+/// `nearest_cat` gives up on cats further away than this many tiles, enough to reach
+/// the Ascicat from the origin wherever its home is
+pub const CAT_SEARCH_RANGE: u32 = 1 << 31;
+
+/// `nearest_landmark` and `find_landmark` give up on landmarks further away than this many tiles.
+/// A miss checks each of the (2 * 4096 / 32 + 1)^2 = 66049 chunks in range once.
+pub const LANDMARK_SEARCH_RANGE: u32 = 1 << 12;
+
+/// Real time per tick used by the frontends (`tick = epoch_millis / CAT_TICK_MILLIS`)
+pub const CAT_TICK_MILLIS: u64 = 2000;
+
+/// The infinite world generator.
+/// Generation is pure (`&self`), so a `World` can be shared across threads;
+/// exploration state such as cat traces lives in `Explorer`.
 pub struct World {
-    cat_visited: HashSet<(i32, i32)>,
+    seed: u64,
+    biomes: BiomeRegistry,
+    biome_mode: BiomeMode,
+    biome_noise: Noise,
+    entities: EntityRegistry,
+    glyphs: GlyphSet,
+    /// Sites exist (and name the landmarks) in every world; they are only drawn on request
+    structures: StructureLayer,
+    draw_structures: bool,
+    chunks: Mutex<ChunkCache>,
 }
 
-impl World {
+/// Builder for worlds with a custom seed, biome set and entities
+pub struct WorldBuilder {
+    seed: u64,
+    biomes: BiomeRegistry,
+    biome_mode: BiomeMode,
+    entities: EntityRegistry,
+    glyphs: GlyphSet,
+    structures: bool,
+    cache_budget: usize,
+}
+
+impl WorldBuilder {
     pub fn new() -> Self {
-        World {
-            cat_visited: HashSet::new(),
+        WorldBuilder {
+            seed: DEFAULT_SEED,
+            biomes: BiomeRegistry::with_defaults(),
+            biome_mode: BiomeMode::PerTile,
+            entities: EntityRegistry::with_defaults(),
+            glyphs: GlyphSet::default(),
+            structures: false,
+            cache_budget: DEFAULT_CACHE_BUDGET,
         }
     }
 
-    /// Generate a tile at the given coordinates
-    pub fn get_tile(&mut self, x: i32, y: i32) -> Tile {
-        // Check if cat has visited this location (check first!)
-        if self.cat_visited.contains(&(x, y)) {
-            return Tile {
-                character: self.get_trace_char(x, y),
-                biome: Biome::CatTrace,
-            };
-        }
+    /// Set the world seed
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
 
-        // Check if cat is present at this coordinate
-        if self.is_cat_location(x, y) {
-            self.cat_visited.insert((x, y));
-            return Tile {
-                character: self.get_cat_char(x, y),
-                biome: Biome::CatPresent,
-            };
+    /// Choose how biomes are laid out (per tile or as noise regions)
+    pub fn biome_mode(mut self, mode: BiomeMode) -> Self {
+        self.biome_mode = mode;
+        self
+    }
+
+    /// Choose the characters tiles are drawn with; the layout stays the same
+    pub fn glyphs(mut self, glyphs: GlyphSet) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Memory budget in bytes for the chunk cache behind `get_region`
+    pub fn cache_budget(mut self, budget_bytes: usize) -> Self {
+        self.cache_budget = budget_bytes;
+        self
+    }
+
+    /// Drop the built-in calm/pattern/glitch biomes
+    pub fn without_default_biomes(mut self) -> Self {
+        self.biomes.clear();
+        self
+    }
+
+    /// Register a biome with its own default weight
+    pub fn biome(mut self, generator: impl BiomeGenerator + 'static) -> Self {
+        self.biomes.register(Box::new(generator));
+        self
+    }
+
+    /// Register a biome with an explicit weight
+    pub fn weighted_biome(mut self, generator: impl BiomeGenerator + 'static, weight: u32) -> Self {
+        self.biomes.register_weighted(Box::new(generator), weight);
+        self
+    }
+
+    /// Add stray cats, birds and glitch mites to the Ascicat
+    pub fn with_creatures(mut self) -> Self {
+        self.entities = EntityRegistry::with_creatures();
+        self
+    }
+
+    /// Lay ruins, monoliths, rivers and roads over the biomes
+    pub fn with_structures(mut self) -> Self {
+        self.structures = true;
+        self
+    }
+
+    /// Drop the built-in entities (including the Ascicat)
+    pub fn without_default_entities(mut self) -> Self {
+        self.entities.clear();
+        self
+    }
+
+    /// Register an entity kind
+    pub fn entity(mut self, kind: impl Entity + 'static) -> Self {
+        self.entities.register(Box::new(kind));
+        self
+    }
+
+    pub fn build(self) -> World {
+        World {
+            seed: self.seed,
+            biomes: self.biomes,
+            biome_mode: self.biome_mode,
+            biome_noise: Noise::new(hash_bytes(self.seed, b"biome-noise")),
+            entities: self.entities,
+            glyphs: self.glyphs,
+            structures: StructureLayer::new(hash_bytes(self.seed, b"structures")),
+            draw_structures: self.structures,
+            chunks: Mutex::new(ChunkCache::with_budget(self.cache_budget)),
         }
+    }
+}
 
-        // Determine biome based on coordinates
-        let hash = self.coord_hash(x, y);
-        let biome_selector = hash % 100;
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        if biome_selector < 60 {
-            // 60% calm empty spaces
-            Tile {
-                character: ' ',
-                biome: Biome::Calm,
-            }
-        } else if biome_selector < 85 {
-            // 25% patterns
-            Tile {
-                character: self.get_pattern_char(x, y, hash),
-                biome: Biome::Pattern,
+impl World {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a world from a numeric seed.
+    /// Every hash, pattern, glitch and the cat position derive from it.
+    pub fn with_seed(seed: u64) -> Self {
+        WorldBuilder::new().seed(seed).build()
+    }
+
+    /// Start building a world with custom biomes
+    pub fn builder() -> WorldBuilder {
+        WorldBuilder::new()
+    }
+
+    /// Create a world from a text seed (e.g. a user name or a bug report id)
+    pub fn from_seed_str(seed: &str) -> Self {
+        Self::with_seed(seed_from_str(seed))
+    }
+
+    /// The seed this world was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Registered biome generators
+    pub fn biomes(&self) -> &BiomeRegistry {
+        &self.biomes
+    }
+
+    /// How biomes are laid out
+    pub fn biome_mode(&self) -> BiomeMode {
+        self.biome_mode
+    }
+
+    /// Registered entity kinds
+    pub fn entities(&self) -> &EntityRegistry {
+        &self.entities
+    }
+
+    /// Characters tiles are drawn with
+    pub fn glyphs(&self) -> GlyphSet {
+        self.glyphs
+    }
+
+    /// Ruins, monoliths, rivers and roads, if the world was built with them
+    pub fn structures(&self) -> Option<&StructureLayer> {
+        self.draw_structures.then_some(&self.structures)
+    }
+
+    /// Change the chunk cache memory budget (in bytes)
+    pub fn set_cache_budget(&mut self, budget_bytes: usize) {
+        self.chunks
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .set_budget(budget_bytes);
+    }
+
+    /// Chunk cache counters
+    pub fn cache_stats(&self) -> CacheStats {
+        self.chunks().stats()
+    }
+
+    /// Lock the chunk cache. A panicking generator never leaves a half-inserted
+    /// chunk behind, so a poisoned lock is safe to keep using.
+    fn chunks(&self) -> MutexGuard<'_, ChunkCache> {
+        self.chunks.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Generate a tile at the given coordinates (cat at tick 0)
+    pub fn get_tile(&self, x: i64, y: i64) -> Tile {
+        self.get_tile_at(x, y, 0)
+    }
+
+    /// Generate a tile with the entities where they are at `tick`
+    pub fn get_tile_at(&self, x: i64, y: i64, tick: u64) -> Tile {
+        self.get_tile_viewed(x, y, tick, None)
+    }
+
+    /// Generate a tile at `tick`, with entities reacting to a viewer at `viewer`
+    pub fn get_tile_viewed(&self, x: i64, y: i64, tick: u64, viewer: Option<(i64, i64)>) -> Tile {
+        // Cats are listed last, so they win over other creatures
+        match self.creatures_in(Rect::new(x, y, 1, 1), tick, viewer).last() {
+            Some(creature) => creature.tile,
+            None => self.base_tile(x, y),
+        }
+    }
+
+    /// Biome tile without entities or traces - this is what the chunk cache stores
+    pub fn base_tile(&self, x: i64, y: i64) -> Tile {
+        let structure = self.structures().and_then(|structures| structures.tile_at(x, y));
+        let tile = structure.unwrap_or_else(|| {
+            // Determine biome based on coordinates
+            let hash = self.coord_hash(x, y);
+            match self.biomes.select(self.biome_selector(x, y, hash)) {
+                Some(generator) => generator.tile_at(x, y, hash),
+                None => Tile::default(),
             }
-        } else {
-            // 15% glitch areas
-            Tile {
-                character: self.get_glitch_char(x, y, hash),
-                biome: Biome::Glitch,
+        });
+        Tile {
+            character: self.glyphs.glyph(tile.character),
+            ..tile
+        }
+    }
+
+    /// Value the biome registry picks from: the tile hash itself,
+    /// or a slot derived from the region noise so neighbours agree
+    fn biome_selector(&self, x: i64, y: i64, hash: u64) -> u64 {
+        match self.biome_mode {
+            BiomeMode::PerTile => hash,
+            BiomeMode::Regions(params) => {
+                let total = self.biomes.total_weight();
+                let (x, y) = (noise::tile_coord(x), noise::tile_coord(y));
+                let value = noise::equalize(self.biome_noise.fractal(x, y, &params));
+                ((value * total as f64) as u64).min(total.saturating_sub(1))
             }
         }
     }
 
     /// Deterministic hash function for coordinates
-    fn coord_hash(&self, x: i32, y: i32) -> u64 {
+    fn coord_hash(&self, x: i64, y: i64) -> u64 {
         // Simple but effective hash mixing
-        let mut h = 0x517cc1b727220a95u64;
-        h = h.wrapping_mul(0x6c62272e07bb0142u64);
+        let mut h = self.seed;
+        h = h.wrapping_mul(HASH_MUL);
         h ^= x as u64;
-        h = h.wrapping_mul(0x6c62272e07bb0142u64);
+        h = h.wrapping_mul(HASH_MUL);
         h ^= y as u64;
-        h = h.wrapping_mul(0x6c62272e07bb0142u64);
+        h = h.wrapping_mul(HASH_MUL);
         h
     }
 
-    /// Check if the cat appears at this special coordinate
-    pub fn is_cat_location(&self, x: i32, y: i32) -> bool {
-        // Only one true Ascicat in the world!
-        let (cat_x, cat_y) = Self::ascicat_position();
-        x == cat_x && y == cat_y
+    /// Check if a cat is at this coordinate at tick 0
+    pub fn is_cat_location(&self, x: i64, y: i64) -> bool {
+        self.is_cat_location_at(x, y, 0)
     }
 
-    /// Returns the one true Ascicat position (deterministic, but schwer zu erraten)
-    pub fn ascicat_position() -> (i32, i32) {
-        // Use a hash of a fixed string to generate unique but stable coordinates
-        let mut h = 0x517cc1b727220a95u64;
-        for b in b"ascicat" {
-            h = h.wrapping_mul(0x6c62272e07bb0142u64);
-            h ^= *b as u64;
-        }
-        let x = ((h >> 16) & 0xFFFF_FFFF) as i32 - 50_000; // Bereich -50_000..+50_000
-        let y = (h & 0xFFFF_FFFF) as i32 - 50_000;
-        (x, y)
+    /// Check if a cat (the Ascicat or a stray) is at this coordinate at `tick`
+    pub fn is_cat_location_at(&self, x: i64, y: i64, tick: u64) -> bool {
+        self.creatures_in(Rect::new(x, y, 1, 1), tick, None)
+            .iter()
+            .any(|creature| creature.tile.biome == Biome::CatPresent)
     }
 
-    /// Get the cat character based on position (different poses)
-    fn get_cat_char(&self, x: i32, y: i32) -> char {
-        let hash = self.coord_hash(x, y);
-        // Use basic ASCII for better WASM compatibility
-        let basic_poses = ['@', 'C', 'c', 'o', 'O'];
-        basic_poses[(hash % basic_poses.len() as u64) as usize]
+    /// Returns the one true Ascicat's home, where it is at tick 0 (deterministic, but schwer zu erraten)
+    pub fn ascicat_position(&self) -> (i64, i64) {
+        // Use a seeded hash of a fixed string to generate unique but stable coordinates
+        let h = hash_bytes(self.seed, b"ascicat");
+        let x = (((h >> 16) & 0xFFFF_FFFF) as i32).wrapping_sub(50_000);
+        let y = ((h & 0xFFFF_FFFF) as i32).wrapping_sub(50_000);
+        (x as i64, y as i64)
     }
 
-    /// Get trace character where cat has walked
-    fn get_trace_char(&self, x: i32, y: i32) -> char {
-        let hash = self.coord_hash(x, y);
-        let traces = ['.', '·', '˙', '∙', '•'];
-        traces[(hash % traces.len() as u64) as usize]
+    /// Where the Ascicat is at `tick`. It walks between seeded waypoints around
+    /// its home, one tile per tick, so any tick is answered in O(1).
+    pub fn cat_position_at(&self, tick: u64) -> (i64, i64) {
+        let (home_x, home_y) = self.ascicat_position();
+        let side = spawn_cell_side(&Ascicat);
+        let id = self.entity_hash(&Ascicat, home_x.div_euclid(side), home_y.div_euclid(side));
+        Ascicat.behaviour().position(id, (home_x, home_y), tick, None)
     }
 
-    /// Generate pattern characters
-    fn get_pattern_char(&self, x: i32, y: i32, hash: u64) -> char {
-        let pattern_type = (hash / 100) % 10;
-        
-        match pattern_type {
-            0 | 1 => {
-                // Checkerboard-like patterns
-                if (x + y) % 2 == 0 { '·' } else { ' ' }
-            }
-            2 | 3 => {
-                // Wave patterns
-                let wave = ((x as f64 * 0.5).sin() + (y as f64 * 0.3).cos()) * 3.0;
-                if wave.abs() < 1.0 { '~' } else { ' ' }
-            }
-            4 => {
-                // Diagonal stripes
-                if (x - y) % 3 == 0 { '/' } else { ' ' }
-            }
-            5 => {
-                // Sparse dots
-                if (x * 7 + y * 11) % 13 == 0 { '•' } else { ' ' }
-            }
-            6 => {
-                // Cross patterns
-                if x % 5 == 0 || y % 5 == 0 { '+' } else { ' ' }
-            }
-            7 => {
-                // Concentric patterns
-                let dist = ((x * x + y * y) as f64).sqrt() as i32;
-                if dist % 10 == 0 { 'o' } else { ' ' }
-            }
-            8 => {
-                // Random-looking sparse characters
-                let chars = ['*', '·', '˙', ' ', ' ', ' '];
-                chars[(hash % chars.len() as u64) as usize]
+    /// Seeded hash for an entity kind in a spawn cell; doubles as the entity's id
+    fn entity_hash(&self, kind: &dyn Entity, cell_x: i64, cell_y: i64) -> u64 {
+        let mut h = hash_bytes(self.seed, kind.name().as_bytes());
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= cell_x as u64;
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= cell_y as u64;
+        h = h.wrapping_mul(HASH_MUL);
+        h
+    }
+
+    /// Spawn table of one kind: every entity whose home lies inside `rect`.
+    /// Kinds that list their homes (`Entity::homes_in`) are asked directly; for the
+    /// rest, only the spawn cells overlapping `rect` are visited.
+    fn spawns_of(&self, index: usize, kind: &dyn Entity, rect: Rect, spawns: &mut Vec<Spawn>) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+
+        let side = spawn_cell_side(kind);
+        if let Some(homes) = kind.homes_in(self, rect) {
+            // Same ids as the cell walk: one entity per cell, hashed by its cell
+            for home in homes.into_iter().filter(|home| rect.contains(home.0, home.1)) {
+                let id = self.entity_hash(kind, home.0.div_euclid(side), home.1.div_euclid(side));
+                spawns.push(Spawn { id, kind: index, home });
             }
-            _ => {
-                // Minimalist single dots
-                if hash % 20 == 0 { '.' } else { ' ' }
+            return;
+        }
+
+        let cells = |start: i64, length: usize| {
+            let last = start.saturating_add_unsigned(length as u64 - 1);
+            start.div_euclid(side)..=last.div_euclid(side)
+        };
+        for cell_y in cells(rect.y, rect.height) {
+            for cell_x in cells(rect.x, rect.width) {
+                let cell = cell_rect(cell_x, cell_y, side);
+                let id = self.entity_hash(kind, cell_x, cell_y);
+                if let Some(home) = kind.spawn(self, cell, id) {
+                    if rect.contains(home.0, home.1) {
+                        spawns.push(Spawn { id, kind: index, home });
+                    }
+                }
             }
         }
     }
 
-    /// Generate glitch characters
-    fn get_glitch_char(&self, _x: i32, _y: i32, hash: u64) -> char {
-        let glitch_intensity = hash % 10;
-        
-        if glitch_intensity < 3 {
-            // Light glitches
-            let chars = ['▓', '▒', '░', '█'];
-            chars[(hash % chars.len() as u64) as usize]
-        } else if glitch_intensity < 6 {
-            // Medium glitches with symbols
-            let chars = ['#', '$', '%', '&', '@', '¤'];
-            chars[(hash % chars.len() as u64) as usize]
-        } else if glitch_intensity < 8 {
-            // Heavy glitches
-            let chars = ['█', '▓', '▒', '░', '▪', '▫'];
-            chars[(hash % chars.len() as u64) as usize]
-        } else {
-            // Rare intense glitches
-            let chars = ['▀', '▄', '▌', '▐', '█', '▓'];
-            chars[(hash % chars.len() as u64) as usize]
+    /// Every entity whose home lies inside `rect`, kind by kind. The cost grows with
+    /// the number of spawn cells covering the rectangle, not with its area in tiles.
+    pub fn spawns_in(&self, rect: Rect) -> Vec<Spawn> {
+        let mut spawns = Vec::new();
+        for (index, kind) in self.entities.iter().enumerate() {
+            self.spawns_of(index, kind, rect, &mut spawns);
         }
+        spawns
+    }
+
+    /// All entities standing inside `rect` at `tick`, cats last. Only spawn cells
+    /// whose entities can reach the rectangle are visited, so the cost grows with
+    /// the area in cells, not tiles.
+    pub fn creatures_in(&self, rect: Rect, tick: u64, viewer: Option<(i64, i64)>) -> Vec<Creature> {
+        self.creatures_where(rect, tick, viewer, |_| true)
+    }
+
+    /// `creatures_in`, limited to the kinds accepted by `keep`
+    fn creatures_where(
+        &self,
+        rect: Rect,
+        tick: u64,
+        viewer: Option<(i64, i64)>,
+        keep: impl Fn(&dyn Entity) -> bool,
+    ) -> Vec<Creature> {
+        let mut creatures = Vec::new();
+        if rect.width == 0 || rect.height == 0 {
+            return creatures;
+        }
+
+        let mut spawns = Vec::new();
+        for (index, kind) in self.entities.iter().enumerate().filter(|(_, kind)| keep(*kind)) {
+            let reach = kind.behaviour().reach().max(0) as u32;
+            self.spawns_of(index, kind, rect.grow(reach), &mut spawns);
+        }
+
+        for spawn in spawns {
+            let Some(kind) = self.entities.get(spawn.kind) else {
+                continue;
+            };
+            let (x, y) = kind.behaviour().position(spawn.id, spawn.home, tick, viewer);
+            if rect.contains(x, y) {
+                creatures.push(Creature {
+                    id: spawn.id,
+                    kind: spawn.kind,
+                    home: spawn.home,
+                    x,
+                    y,
+                    tile: Tile::new(self.glyphs.glyph(kind.glyph(tick, self.coord_hash(x, y))), kind.biome()),
+                });
+            }
+        }
+
+        creatures.sort_by_key(|creature| creature.tile.biome == Biome::CatPresent);
+        creatures
+    }
+
+    /// Get trace character where cat has walked; older steps (by `age` in ticks) fade to lighter glyphs
+    pub(crate) fn get_trace_char(&self, age: u64) -> char {
+        // Freshest first
+        let traces = ['•', '∙', '·', '˙', '.'];
+        let index = age.saturating_mul(traces.len() as u64) / (TRAIL_TICKS + 1);
+        self.glyphs.glyph(traces[(index as usize).min(traces.len() - 1)])
     }
 
     /// Get a rectangular region of the world
-    pub fn get_region(&mut self, x_start: i32, y_start: i32, width: usize, height: usize) -> Vec<Vec<Tile>> {
-        let mut region = Vec::with_capacity(height);
-        
-        for y in 0..height {
-            let mut row = Vec::with_capacity(width);
-            for x in 0..width {
-                let tile = self.get_tile(x_start + x as i32, y_start + y as i32);
-                row.push(tile);
+    pub fn get_region(&self, x_start: i64, y_start: i64, width: usize, height: usize) -> Vec<Vec<Tile>> {
+        let mut region = Region::new(x_start, y_start, width, height);
+        self.fill_region(&mut region);
+        region.to_rows()
+    }
+
+    /// Fill a caller-provided region buffer; reuse it between frames to avoid allocations
+    pub fn fill_region(&self, region: &mut Region) {
+        self.fill_region_at(region, 0);
+    }
+
+    /// Fill a region buffer with the entities where they are at `tick`
+    pub fn fill_region_at(&self, region: &mut Region, tick: u64) {
+        self.fill_region_viewed(region, tick, None);
+    }
+
+    /// Fill a region buffer at `tick`, with entities reacting to a viewer at `viewer`
+    pub fn fill_region_viewed(&self, region: &mut Region, tick: u64, viewer: Option<(i64, i64)>) {
+        #[cfg(feature = "parallel")]
+        {
+            if region.width() * region.height() >= PARALLEL_MIN_TILES {
+                self.fill_base_parallel(region);
+            } else {
+                self.fill_base_cached(region);
             }
-            region.push(row);
         }
-        
+        #[cfg(not(feature = "parallel"))]
+        self.fill_base_cached(region);
+
+        // Entities are not cached, they are placed once per region
+        for creature in self.creatures_in(region.rect(), tick, viewer) {
+            if let Some(local) = region.local(creature.x, creature.y) {
+                region[local] = creature.tile;
+            }
+        }
+    }
+
+    /// Copy base tiles out of cached chunks. Hits are copied under the cache lock;
+    /// misses are generated without it, so other threads are not held up meanwhile.
+    fn fill_base_cached(&self, region: &mut Region) {
+        let (x_start, y_start) = region.origin();
+        let columns = chunk_spans(x_start, region.width());
+        let rows = chunk_spans(y_start, region.height());
+
+        let mut missing = Vec::new();
+        {
+            let mut chunks = self.chunks();
+            for row in &rows {
+                for column in &columns {
+                    match chunks.get(ChunkCoord { x: column.chunk, y: row.chunk }) {
+                        Some(chunk) => copy_from_chunk(region, chunk, column, row),
+                        None => missing.push((column, row)),
+                    }
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let generated: Vec<Chunk> = missing
+            .into_iter()
+            .map(|(column, row)| {
+                let chunk = Chunk::generate(ChunkCoord { x: column.chunk, y: row.chunk }, |x, y| self.base_tile(x, y));
+                copy_from_chunk(region, &chunk, column, row);
+                chunk
+            })
+            .collect();
+
+        let mut chunks = self.chunks();
+        for chunk in generated {
+            chunks.insert(chunk);
+        }
+    }
+
+    /// Generate base tiles row by row on all cores. Huge regions would only
+    /// thrash the cache, so tiles are generated directly - same output either way.
+    #[cfg(feature = "parallel")]
+    fn fill_base_parallel(&self, region: &mut Region) {
+        use rayon::prelude::*;
+
+        let (x_start, y_start) = region.origin();
+        let width = region.width();
         region
+            .tiles_mut()
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let world_y = y_start.wrapping_add(y as i64);
+                for (x, tile) in row.iter_mut().enumerate() {
+                    *tile = self.base_tile(x_start.wrapping_add(x as i64), world_y);
+                }
+            });
     }
 
     /// Render a region to ASCII string
-    pub fn render_region(&mut self, x_start: i32, y_start: i32, width: usize, height: usize) -> String {
-        let region = self.get_region(x_start, y_start, width, height);
-        let mut output = String::with_capacity(width * height + height);
-        
-        for row in region {
-            for tile in row {
-                output.push(tile.character);
+    pub fn render_region(&self, x_start: i64, y_start: i64, width: usize, height: usize) -> String {
+        let mut region = Region::new(x_start, y_start, width, height);
+        self.fill_region(&mut region);
+        region.to_string()
+    }
+
+    /// Find nearby cat locations at tick 0 (for exploration)
+    pub fn find_cat_nearby(&self, x_center: i64, y_center: i64, radius: i32) -> Vec<(i64, i64)> {
+        self.find_cat_nearby_at(x_center, y_center, radius, 0)
+    }
+
+    /// Find cat locations at `tick` within `radius` tiles (a square), row by row.
+    /// Answered from the spawn tables, so even a radius of 50,000 is cheap.
+    pub fn find_cat_nearby_at(&self, x_center: i64, y_center: i64, radius: i32, tick: u64) -> Vec<(i64, i64)> {
+        if radius < 0 {
+            return Vec::new();
+        }
+
+        self.cats_in(Rect::around(x_center, y_center, radius as u32), tick)
+    }
+
+    /// Cat locations inside `area` at `tick`, row by row
+    fn cats_in(&self, area: Rect, tick: u64) -> Vec<(i64, i64)> {
+        let mut cats: Vec<_> = self
+            .creatures_where(area, tick, None, |kind| kind.biome() == Biome::CatPresent)
+            .into_iter()
+            .map(|cat| (cat.x, cat.y))
+            .collect();
+        cats.sort_by_key(|&(x, y)| (y, x));
+        cats.dedup();
+        cats
+    }
+
+    /// The cat closest to (x, y) at tick 0
+    pub fn nearest_cat(&self, x: i64, y: i64) -> Option<(i64, i64)> {
+        self.nearest_cat_at(x, y, 0)
+    }
+
+    /// The cat closest to (x, y) at `tick` (straight-line distance, ties broken row by row),
+    /// or `None` if there is none within `CAT_SEARCH_RANGE`. Searches squares of
+    /// doubling size, so the cost grows with the spawn cells up to the nearest cat.
+    pub fn nearest_cat_at(&self, x: i64, y: i64, tick: u64) -> Option<(i64, i64)> {
+        if !self.entities.iter().any(|kind| kind.biome() == Biome::CatPresent) {
+            return None;
+        }
+
+        let distance = |(cat_x, cat_y): (i64, i64)| {
+            let (dx, dy) = (cat_x.abs_diff(x) as u128, cat_y.abs_diff(y) as u128);
+            (dx * dx + dy * dy, cat_y, cat_x)
+        };
+        let mut radius = CHUNK_SIZE as u32;
+        loop {
+            let nearest = self.cats_in(Rect::around(x, y, radius), tick).into_iter().min_by_key(|&cat| distance(cat));
+            // Anything outside the square is further away than `radius`
+            match nearest {
+                Some(cat) if distance(cat).0 <= radius as u128 * radius as u128 => return Some(cat),
+                _ if radius >= CAT_SEARCH_RANGE => return nearest,
+                _ => radius = (radius * 2).min(CAT_SEARCH_RANGE),
             }
-            output.push('\n');
         }
-        
-        output
     }
 
-    /// Find nearby cat locations (for exploration)
-    pub fn find_cat_nearby(&self, x_center: i32, y_center: i32, radius: i32) -> Vec<(i32, i32)> {
-        let mut cat_locations = Vec::new();
-        
-        for y in (y_center - radius)..=(y_center + radius) {
-            for x in (x_center - radius)..=(x_center + radius) {
-                if self.is_cat_location(x, y) {
-                    cat_locations.push((x, y));
+    /// Landmarks inside `rect`, row of chunks by row of chunks. They stand at the
+    /// structure sites, whether or not the world draws its structures.
+    pub fn landmarks_in(&self, rect: Rect) -> Vec<Landmark> {
+        self.structures.sites_in(rect).iter().map(Landmark::at).collect()
+    }
+
+    /// The landmark closest to (x, y), or `None` if there is none within `LANDMARK_SEARCH_RANGE`
+    pub fn nearest_landmark(&self, x: i64, y: i64) -> Option<Landmark> {
+        self.find_landmark_where(x, y, |_| true)
+    }
+
+    /// The landmark called `name` (ignoring case) closest to (x, y), within `LANDMARK_SEARCH_RANGE`
+    pub fn find_landmark(&self, name: &str, x: i64, y: i64) -> Option<Landmark> {
+        self.find_landmark_where(x, y, |landmark| landmark.is_called(name))
+    }
+
+    /// Closest landmark accepted by `keep`, searching outward in square rings of doubling
+    /// size so every chunk in range is visited at most once
+    fn find_landmark_where(&self, x: i64, y: i64, keep: impl Fn(&Landmark) -> bool) -> Option<Landmark> {
+        let distance = |landmark: &Landmark| {
+            let (dx, dy) = (landmark.x.abs_diff(x) as u128, landmark.y.abs_diff(y) as u128);
+            (dx * dx + dy * dy, landmark.y, landmark.x)
+        };
+        let mut nearest: Option<Landmark> = None;
+        let mut searched = None;
+        let mut radius = CHUNK_SIZE as u32;
+        loop {
+            let rects = match searched {
+                None => vec![Rect::around(x, y, radius)],
+                Some(inner) => ring(x, y, inner, radius).to_vec(),
+            };
+            for rect in rects {
+                for landmark in self.landmarks_in(rect).into_iter().filter(&keep) {
+                    if nearest.as_ref().is_none_or(|best| distance(&landmark) < distance(best)) {
+                        nearest = Some(landmark);
+                    }
+                }
+            }
+            // Anything outside the square is further away than `radius`
+            match nearest {
+                Some(ref landmark) if distance(landmark).0 <= radius as u128 * radius as u128 => return nearest,
+                _ if radius >= LANDMARK_SEARCH_RANGE => return nearest,
+                _ => {
+                    searched = Some(radius);
+                    radius = (radius * 2).min(LANDMARK_SEARCH_RANGE);
                 }
             }
         }
-        
-        cat_locations
     }
 }
 
+/// The part of a region's rows or columns that falls in one chunk
+struct ChunkSpan {
+    /// Chunk coordinate along the axis
+    chunk: i64,
+    /// First tile of the span within the chunk
+    local: usize,
+    /// First tile of the span within the region
+    offset: usize,
+    len: usize,
+}
+
+/// Split `length` tiles from `start` into one span per chunk
+fn chunk_spans(start: i64, length: usize) -> Vec<ChunkSpan> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    while offset < length {
+        let coord = start.wrapping_add(offset as i64);
+        let chunk = coord.div_euclid(CHUNK_SIZE as i64);
+        let local = coord.rem_euclid(CHUNK_SIZE as i64) as usize;
+        let len = (CHUNK_SIZE - local).min(length - offset);
+        spans.push(ChunkSpan { chunk, local, offset, len });
+        offset += len;
+    }
+    spans
+}
+
+/// Copy the block of `chunk` at `column` x `row` into the region
+fn copy_from_chunk(region: &mut Region, chunk: &Chunk, column: &ChunkSpan, row: &ChunkSpan) {
+    for y in 0..row.len {
+        let source = &chunk.row(row.local + y)[column.local..column.local + column.len];
+        region.row_mut(row.offset + y)[column.offset..column.offset + column.len].copy_from_slice(source);
+    }
+}
+
+/// The four strips covering `Rect::around(x, y, outer)` minus `Rect::around(x, y, inner)`
+fn ring(x: i64, y: i64, inner: u32, outer: u32) -> [Rect; 4] {
+    let outside = Rect::around(x, y, outer);
+    let inside = Rect::around(x, y, inner);
+    let band = (outer - inner) as usize;
+    let below = y.saturating_add_unsigned(inner as u64 + 1);
+    let right = x.saturating_add_unsigned(inner as u64 + 1);
+    [
+        Rect::new(outside.x, outside.y, outside.width, band),
+        Rect::new(outside.x, below, outside.width, band),
+        Rect::new(outside.x, inside.y, band, inside.height),
+        Rect::new(right, inside.y, band, inside.height),
+    ]
+}
+
+/// Side of a kind's spawn cells in tiles
+fn spawn_cell_side(kind: &dyn Entity) -> i64 {
+    kind.spawn_cell_chunks().max(1) as i64 * CHUNK_SIZE as i64
+}
+
+/// Tiles of spawn cell (cell_x, cell_y), clipped to the edges of the world
+fn cell_rect(cell_x: i64, cell_y: i64, side: i64) -> Rect {
+    let clip = |cell: i64| {
+        let start = cell * side;
+        let end = start.saturating_add(side - 1);
+        (start, end.abs_diff(start) as usize + 1)
+    };
+    let (x, width) = clip(cell_x);
+    let (y, height) = clip(cell_y);
+    Rect::new(x, y, width, height)
+}
+
+/// Fold bytes into a hash, starting from `h`
+fn hash_bytes(mut h: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        h = h.wrapping_mul(HASH_MUL);
+        h ^= *b as u64;
+    }
+    h
+}
+
+/// Derive a numeric seed from text; the empty string maps to `DEFAULT_SEED`
+pub fn seed_from_str(seed: &str) -> u64 {
+    hash_bytes(DEFAULT_SEED, seed.as_bytes())
+}
+
 impl Default for World {
     fn default() -> Self {
         Self::new()
@@ -245,11 +914,12 @@ impl Default for World {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::noise::NoiseParams;
 
     #[test]
     fn test_deterministic_generation() {
-        let mut world1 = World::new();
-        let mut world2 = World::new();
+        let world1 = World::new();
+        let world2 = World::new();
         
         let tile1 = world1.get_tile(42, 17);
         let tile2 = world2.get_tile(42, 17);
@@ -290,7 +960,7 @@ mod tests {
 
     #[test]
     fn test_region_generation() {
-        let mut world = World::new();
+        let world = World::new();
         let region = world.get_region(0, 0, 10, 10);
         
         assert_eq!(region.len(), 10);
@@ -298,19 +968,397 @@ mod tests {
     }
 
     #[test]
-    fn test_cat_trace_persistence() {
-        let mut world = World::new();
-        
-        // Find a cat location
-        let cats = world.find_cat_nearby(0, 0, 1000);
-        if let Some(&(x, y)) = cats.first() {
-            // Visit the cat
-            let tile1 = world.get_tile(x, y);
-            assert_eq!(tile1.biome, Biome::CatPresent);
-            
-            // Visit again - should leave a trace
-            let tile2 = world.get_tile(x, y);
-            assert_eq!(tile2.biome, Biome::CatTrace);
+    fn test_cached_region_matches_tiles() {
+        let cached = World::builder().cache_budget(0).build();
+        let direct = World::new();
+
+        // Odd origin and size so the region straddles chunk borders
+        let region = cached.get_region(-45, 13, 70, 40);
+        let again = cached.get_region(-45, 13, 70, 40);
+        for (y, row) in region.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                assert_eq!(*tile, direct.get_tile(-45 + x as i64, 13 + y as i64));
+            }
+        }
+        assert_eq!(region, again);
+        assert!(cached.cache_stats().evictions > 0);
+    }
+
+    #[test]
+    fn test_far_coordinates() {
+        let world = World::new();
+        // Tiles 2^32 apart no longer alias
+        assert_ne!(world.coord_hash(1 << 32, 0), world.coord_hash(0, 0));
+        assert_ne!(world.coord_hash(0, -(1 << 40)), world.coord_hash(0, 0));
+
+        // Regions at the edges of the world generate (and wrap past them) without overflowing
+        for world in [World::new(), World::builder().biome_mode(BiomeMode::Regions(NoiseParams::default())).build()] {
+            let region = world.get_region(i64::MAX - 40, i64::MIN, 70, 3);
+            assert_eq!(region[2][40], world.get_tile(i64::MAX, i64::MIN + 2));
+            assert_eq!(region[0][41], world.get_tile(i64::MIN, i64::MIN));
+            world.creatures_in(Rect::new(i64::MIN, i64::MAX - 99, 100, 100), 12345, Some((i64::MAX, i64::MAX)));
+            world.find_cat_nearby(i64::MAX, i64::MIN, 5000);
+        }
+    }
+
+    #[test]
+    fn test_fill_region_reuses_buffer() {
+        let world = World::new();
+        let mut region = Region::new(0, 0, 30, 10);
+        world.fill_region(&mut region);
+        assert_eq!(region.to_rows(), world.get_region(0, 0, 30, 10));
+
+        region.set_bounds(-7, 3, 12, 4);
+        world.fill_region(&mut region);
+        assert_eq!(region[(0, 0)], world.get_tile(-7, 3));
+        assert_eq!(region.to_string(), world.render_region(-7, 3, 12, 4));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let world = World::new();
+        let (cat_x, cat_y) = world.ascicat_position();
+        // 600x300 is above PARALLEL_MIN_TILES
+        let mut big = Region::new(cat_x - 300, cat_y - 150, 600, 300);
+        world.fill_region(&mut big);
+
+        let mut serial = Region::new(cat_x - 300, cat_y - 150, 600, 300);
+        world.fill_base_cached(&mut serial);
+        serial[(300, 150)] = world.get_tile(cat_x, cat_y);
+
+        assert_eq!(big.to_string(), serial.to_string());
+        assert_eq!(world.find_cat_nearby(cat_x, cat_y, 40), vec![(cat_x, cat_y)]);
+    }
+
+    #[test]
+    fn test_world_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<World>();
+
+        let world = std::sync::Arc::new(World::new());
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let world = world.clone();
+                std::thread::spawn(move || world.render_region(i * 50, 0, 50, 20))
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), world.render_region(i as i64 * 50, 0, 50, 20));
+        }
+    }
+
+    #[test]
+    fn test_default_seed_matches_new() {
+        // Tiles and Ascicat home of the original, unseeded world (before seeds existed):
+        // changing them breaks every saved seed
+        let world = World::new();
+        assert_eq!(world.seed(), DEFAULT_SEED);
+        assert_eq!(world.render_region(0, 0, 12, 3), "    ·    ▒o▌\n          ▀ \n·   ▓ ·     \n");
+        assert_eq!(world.ascicat_position(), (-102009382, 959029586));
+        assert!(world.is_cat_location(-102009382, 959029586));
+        let tile = world.get_tile(-1000, 250);
+        assert_eq!((tile.biome, tile.pattern), (Biome::Pattern, PatternKind::Wave));
+        assert_eq!(World::from_seed_str("").seed(), DEFAULT_SEED);
+    }
+
+    #[test]
+    fn test_seeds_produce_different_worlds() {
+        let world1 = World::with_seed(1);
+        let world2 = World::with_seed(2);
+
+        assert_ne!(world1.render_region(0, 0, 40, 20), world2.render_region(0, 0, 40, 20));
+        assert_ne!(world1.ascicat_position(), world2.ascicat_position());
+        assert_eq!(World::from_seed_str("alice").seed(), World::from_seed_str("alice").seed());
+        assert_ne!(World::from_seed_str("alice").seed(), World::from_seed_str("bob").seed());
+    }
+
+    #[test]
+    fn test_builder_custom_biomes() {
+        struct Ocean;
+
+        impl BiomeGenerator for Ocean {
+            fn name(&self) -> &str {
+                "ocean"
+            }
+
+            fn biome(&self) -> Biome {
+                Biome::Custom("ocean")
+            }
+
+            fn weight(&self) -> u32 {
+                1
+            }
+
+            fn char_at(&self, _x: i64, _y: i64, _hash: u64) -> char {
+                '≈'
+            }
+        }
+
+        let world = World::builder().without_default_biomes().biome(Ocean).build();
+        let tile = world.get_tile(3, 4);
+
+        assert_eq!(tile.biome, Biome::Custom("ocean"));
+        assert_eq!(tile.character, '≈');
+    }
+
+    #[test]
+    fn test_glyph_sets_keep_the_layout() {
+        let rect = Rect::new(-150, 40, 160, 90);
+        let unicode = World::builder().with_creatures().build();
+        let mut expected = Region::new(rect.x, rect.y, rect.width, rect.height);
+        unicode.fill_region_at(&mut expected, 7);
+
+        for glyphs in GlyphSet::ALL {
+            let world = World::builder().with_creatures().glyphs(glyphs).build();
+            assert_eq!(world.glyphs(), glyphs);
+            let mut region = Region::new(rect.x, rect.y, rect.width, rect.height);
+            world.fill_region_at(&mut region, 7);
+            for (tile, original) in region.rows().flatten().zip(expected.rows().flatten()) {
+                assert_eq!(Tile { character: original.character, ..*tile }, *original);
+                assert_eq!(tile.character, glyphs.glyph(original.character));
+                assert!(glyphs.contains(tile.character));
+            }
+            assert_eq!(world.get_trace_char(0), glyphs.glyph(unicode.get_trace_char(0)));
+        }
+
+        let ascii = World::builder().glyphs(GlyphSet::Ascii).build();
+        assert!(ascii.render_region(-500, -500, 200, 100).is_ascii());
+    }
+
+    #[test]
+    fn test_landmarks() {
+        let world = World::with_seed(99);
+        let rect = Rect::new(-300, -200, 600, 400);
+        let landmarks = world.landmarks_in(rect);
+        assert!(landmarks.len() > 40, "{} landmarks", landmarks.len());
+        assert!(landmarks.iter().all(|landmark| rect.contains(landmark.x, landmark.y)));
+        assert_eq!(landmarks, World::builder().seed(99).with_structures().build().landmarks_in(rect));
+        assert_ne!(landmarks, World::with_seed(100).landmarks_in(rect));
+
+        // Landmarks stand on their structure
+        let drawn = World::builder().seed(99).with_structures().build();
+        for landmark in &landmarks {
+            assert_eq!(drawn.base_tile(landmark.x, landmark.y).biome, Biome::Structure, "{}", landmark);
+        }
+
+        for (x, y) in [(0, 0), (-250, 170), (123, -45)] {
+            let distance = |landmark: &Landmark| (landmark.x - x).pow(2) + (landmark.y - y).pow(2);
+            let nearest = world.nearest_landmark(x, y).unwrap();
+            let expected = landmarks.iter().map(distance).min().unwrap();
+            assert_eq!(distance(&nearest), expected);
+        }
+
+        let target = &landmarks[landmarks.len() / 2];
+        assert_eq!(world.find_landmark(&target.name.to_lowercase(), 0, 0).as_ref(), Some(target));
+        assert_eq!(world.find_landmark("Nowhere Special", 0, 0), None);
+
+        // Rings tile the square without overlap, so a distant name is found after several of them
+        let (inside, outside) = (Rect::around(5, -5, 3), Rect::around(5, -5, 7));
+        let strips = ring(5, -5, 3, 7);
+        for ty in outside.y..outside.y + outside.height as i64 {
+            for tx in outside.x..outside.x + outside.width as i64 {
+                let covered = strips.iter().filter(|strip| strip.contains(tx, ty)).count();
+                assert_eq!(covered, if inside.contains(tx, ty) { 0 } else { 1 }, "({}, {})", tx, ty);
+            }
+        }
+        let far = world.landmarks_in(Rect::new(2000, -1200, 400, 400)).remove(0);
+        assert_eq!(world.find_landmark(&far.name, 0, 0), Some(far));
+    }
+
+    #[test]
+    fn test_structures_override_biomes() {
+        let plain = World::new();
+        let world = World::builder().with_structures().build();
+        assert!(plain.structures().is_none());
+        let layer = world.structures().unwrap();
+
+        let mut region = Region::new(-200, -100, 400, 200);
+        world.fill_region(&mut region);
+        let mut structures = 0;
+        for (y, row) in region.rows().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let (x, y) = (x as i64 - 200, y as i64 - 100);
+                assert_eq!(*tile, world.get_tile(x, y));
+                match layer.tile_at(x, y) {
+                    Some(structure) => {
+                        assert_eq!(*tile, structure);
+                        structures += 1;
+                    }
+                    None => assert_eq!(*tile, plain.get_tile(x, y)),
+                }
+            }
+        }
+        assert!(structures > 1000, "{} structure tiles", structures);
+        assert!(plain.render_region(-200, -100, 400, 200).chars().all(|c| c != '≈'));
+
+        let ascii = World::builder().with_structures().glyphs(GlyphSet::Ascii).build();
+        assert!(ascii.render_region(-200, -100, 400, 200).is_ascii());
+    }
+
+    #[test]
+    fn test_region_mode_forms_zones() {
+        let per_tile = World::new();
+        let regions = World::builder()
+            .biome_mode(BiomeMode::Regions(NoiseParams::default()))
+            .build();
+
+        // Count horizontal neighbours that share a biome
+        let mut same_per_tile = 0;
+        let mut same_regions = 0;
+        let mut calm = 0;
+        for y in 0..100 {
+            for x in 0..100 {
+                if per_tile.get_tile(x, y).biome == per_tile.get_tile(x + 1, y).biome {
+                    same_per_tile += 1;
+                }
+                let biome = regions.get_tile(x, y).biome;
+                if biome == regions.get_tile(x + 1, y).biome {
+                    same_regions += 1;
+                }
+                if biome == Biome::Calm {
+                    calm += 1;
+                }
+            }
+        }
+
+        assert!(same_regions > 9_000);
+        assert!(same_per_tile < 6_000);
+        // Weights still hold roughly
+        assert!((3_000..9_000).contains(&calm));
+    }
+
+    #[test]
+    fn test_cat_walk() {
+        let world = World::new();
+        let (home_x, home_y) = world.ascicat_position();
+        assert!(home_x.abs() <= CAT_HOME_RANGE && home_y.abs() <= CAT_HOME_RANGE);
+        assert_eq!(world.cat_position_at(0), (home_x, home_y));
+
+        let mut moved = false;
+        let mut previous = world.cat_position_at(0);
+        for tick in 1..crate::entity::CAT_LEG_TICKS * 20 {
+            let (x, y) = world.cat_position_at(tick);
+            // One tile per tick at most, never far from home
+            assert!((x - previous.0).abs() <= 1 && (y - previous.1).abs() <= 1);
+            let radius = crate::entity::CAT_ROAM_RADIUS as i64;
+            assert!((x - home_x).abs() <= radius && (y - home_y).abs() <= radius);
+            moved |= (x, y) != previous;
+            previous = (x, y);
         }
+        assert!(moved);
+
+        // Far-future ticks are just as cheap and deterministic
+        let far = u64::MAX - 3;
+        assert_eq!(world.cat_position_at(far), World::new().cat_position_at(far));
+    }
+
+    #[test]
+    fn test_cat_queries_agree_at_tick() {
+        let world = World::new();
+        let tick = 12_345;
+        let (cat_x, cat_y) = world.cat_position_at(tick);
+
+        assert!(world.is_cat_location_at(cat_x, cat_y, tick));
+        assert_eq!(world.get_tile_at(cat_x, cat_y, tick).biome, Biome::CatPresent);
+        assert_eq!(world.find_cat_nearby_at(cat_x + 3, cat_y - 2, 5, tick), vec![(cat_x, cat_y)]);
+
+        let mut region = Region::new(cat_x - 4, cat_y - 4, 9, 9);
+        world.fill_region_at(&mut region, tick);
+        assert_eq!(region[(4, 4)].biome, Biome::CatPresent);
+    }
+
+    #[test]
+    fn test_cat_search_uses_spawn_tables() {
+        let world = World::builder().with_creatures().build();
+
+        // A 100,001-tile square is answered from spawn cells, not by visiting tiles
+        let cats = world.find_cat_nearby(0, 0, 50_000);
+        assert!(cats.len() > 1_000);
+        assert!(cats.windows(2).all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+        assert!(cats.iter().all(|&(x, y)| world.is_cat_location(x, y)));
+
+        let (home_x, home_y) = world.ascicat_position();
+        assert!(world.find_cat_nearby(home_x + 1_000, home_y - 1_000, 50_000).contains(&(home_x, home_y)));
+        let spawns = world.spawns_in(Rect::around(home_x, home_y, 40));
+        assert!(spawns.iter().any(|spawn| spawn.home == (home_x, home_y) && spawn.kind == 0));
+
+        // The nearest cat is among the ones found, and nothing is closer
+        let nearest = world.nearest_cat(0, 0).unwrap();
+        let distance = |(x, y): (i64, i64)| x * x + y * y;
+        assert!(cats.iter().all(|&cat| distance(cat) >= distance(nearest)));
+        assert_eq!(world.find_cat_nearby(0, 0, -1), vec![]);
+
+        // The default world only has the Ascicat, wherever you look from; it lists its
+        // home directly, so even the whole i32 range is one check
+        let lonely = World::new();
+        assert_eq!(lonely.find_cat_nearby(0, 0, i32::MAX), vec![lonely.ascicat_position()]);
+        assert_eq!(lonely.nearest_cat(900_000, -900_000), Some(lonely.ascicat_position()));
+        let tick = 777;
+        assert_eq!(lonely.nearest_cat_at(0, 0, tick), Some(lonely.cat_position_at(tick)));
+        assert_eq!(World::builder().without_default_entities().build().nearest_cat(0, 0), None);
+    }
+
+    #[test]
+    fn test_creatures_spawn_per_chunk() {
+        let world = World::builder().with_creatures().build();
+        let area = Rect::new(-300, -300, 600, 600);
+        let creatures = world.creatures_in(area, 7, None);
+
+        let birds: Vec<_> = creatures.iter().filter(|c| c.tile.biome == Biome::Creature("bird")).collect();
+        let mites: Vec<_> = creatures.iter().filter(|c| c.tile.biome == Biome::Creature("glitch-mite")).collect();
+        assert!(!birds.is_empty() && !mites.is_empty());
+        assert!(birds.iter().all(|c| world.base_tile(c.home.0, c.home.1).biome == Biome::Calm));
+        assert!(mites.iter().all(|c| world.base_tile(c.home.0, c.home.1).biome == Biome::Glitch));
+
+        // Same answer tile by tile, region by region and on a second call
+        let mut region = Region::new(area.x, area.y, area.width, area.height);
+        world.fill_region_at(&mut region, 7);
+        for creature in &creatures {
+            let local = region.local(creature.x, creature.y).unwrap();
+            assert_eq!(region[local].biome, world.get_tile_at(creature.x, creature.y, 7).biome);
+        }
+        assert_eq!(world.creatures_in(area, 7, None), creatures);
+
+        // The default world only has the Ascicat
+        assert!(World::new().creatures_in(area, 7, None).is_empty());
+    }
+
+    #[test]
+    fn test_custom_entity() {
+        use crate::entity::Behaviour;
+
+        struct Beacon;
+
+        impl Entity for Beacon {
+            fn name(&self) -> &str {
+                "beacon"
+            }
+
+            fn biome(&self) -> Biome {
+                Biome::Creature("beacon")
+            }
+
+            fn behaviour(&self) -> Behaviour {
+                Behaviour::Path { waypoints: 1, radius: 0 }
+            }
+
+            fn spawn(&self, _world: &World, cell: Rect, _hash: u64) -> Option<(i64, i64)> {
+                Some((cell.x, cell.y))
+            }
+
+            fn glyph(&self, tick: u64, _hash: u64) -> char {
+                if tick.is_multiple_of(2) { '!' } else { 'i' }
+            }
+        }
+
+        let world = World::builder().without_default_entities().entity(Beacon).build();
+        assert_eq!(world.entities().len(), 1);
+        assert_eq!(world.get_tile_at(64, -32, 0).character, '!');
+        assert_eq!(world.get_tile_at(64, -32, 1).character, 'i');
+        assert_eq!(world.creatures_in(Rect::new(0, 0, 64, 64), 0, None).len(), 4);
+
+        let (cat_x, cat_y) = world.ascicat_position();
+        assert!(!world.is_cat_location(cat_x, cat_y));
     }
 }